@@ -2,8 +2,28 @@ mod login;
 mod downloader;
 mod mc_downloader;
 mod mc_manager;
+mod game_process;
+mod crash;
+mod instance;
+mod history;
+mod launch_script;
+mod import;
+mod archive;
+mod copy_on_write;
+mod icon;
+mod dirs;
 
 pub use login::*;
 pub use downloader::*;
 pub use mc_downloader::*;
 pub use mc_manager::*;
+pub use game_process::*;
+pub use crash::*;
+pub use instance::*;
+pub use history::*;
+pub use launch_script::*;
+pub use import::*;
+pub use archive::*;
+pub use copy_on_write::*;
+pub use icon::*;
+pub use dirs::*;
@@ -0,0 +1,233 @@
+use std::io::{Read, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::SystemTime;
+use serde::{Deserialize, Serialize};
+use crate::backend::{create_instance, discard_failed_import, game_dir, instance_dir, instance_game_dir, load_instance, McError, McResult, save_instance, sha1_hex, unix_millis};
+use crate::model::{Instance, InstanceSettings};
+
+const EXPORTS_DIR: &str = "exports";
+const MANIFEST_ENTRY: &str = "manifest.json";
+const INSTANCE_ENTRY: &str = "instance.toml";
+/// Directory of the game files inside an archive.
+const GAME_DIR_ENTRY: &str = "minecraft";
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+/// Game directory folders an archive can contain.
+pub const ARCHIVE_FOLDERS: [&str; 5] = ["mods", "config", "resourcepacks", "shaderpacks", "saves"];
+/// Caches, logs and files with credentials, which are never exported.
+pub const EXCLUDED_NAMES: &[&str] = &[
+	"logs",
+	"crash-reports",
+	"cache",
+	".cache",
+	".fabric",
+	"launcher_accounts.json",
+	"launcher_profiles.json",
+	"accounts.json"
+];
+
+/// JVM arguments that run commands or load code from outside the game.
+const UNSAFE_JVM_ARGS: &[&str] = &["-XX:OnError", "-XX:OnOutOfMemoryError", "-javaagent", "-agentpath", "-agentlib"];
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveManifest {
+	format_version: u32,
+	files: Vec<ArchiveFile>
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ArchiveFile {
+	/// Path relative to the game directory with `/` separators.
+	path: String,
+	sha1: String,
+	size: u64
+}
+
+/// Writes the configuration of an instance and the given `folders` of its game directory to a zip
+/// in the exports folder of the instance, returning its path.
+pub async fn export_instance(id: String, folders: Vec<String>) -> McResult<PathBuf> {
+	let instance = load_instance(&id).await?;
+	let game_dir = instance_game_dir(&instance);
+	let mut exported = instance.clone();
+	strip_local_settings(&mut exported.settings);
+	exported.icon = None;
+	let instance_toml = toml::to_string(&exported).unwrap();
+
+	let dir = instance_dir(&id).join(EXPORTS_DIR);
+	tokio::fs::create_dir_all(&dir).await?;
+	let path = dir.join(format!("{}-{}.zip", id, unix_millis(SystemTime::now()) / 1000));
+	let archive_path = path.clone();
+	tokio::task::spawn_blocking(move || write_archive(&archive_path, &game_dir, &folders, &instance_toml))
+		.await
+		.map_err(|err| McError::Process(format!("export failed: {}", err)))??;
+	Ok(path)
+}
+
+fn write_archive(path: &Path, game_dir: &Path, folders: &[String], instance_toml: &str) -> McResult<()> {
+	let mut zip = zip::ZipWriter::new(std::fs::File::create(path)?);
+	let options = zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+	let mut files = Vec::new();
+
+	let mut pending: Vec<PathBuf> = folders.iter()
+		.filter(|folder| ARCHIVE_FOLDERS.contains(&folder.as_str()))
+		.map(|folder| game_dir.join(folder))
+		.filter(|dir| dir.is_dir())
+		.collect();
+	while let Some(dir) = pending.pop() {
+		for entry in std::fs::read_dir(&dir)? {
+			let entry = entry?;
+			let name = entry.file_name();
+			if EXCLUDED_NAMES.iter().any(|excluded| name == *excluded) {
+				continue;
+			}
+			if entry.file_type()?.is_dir() {
+				pending.push(entry.path());
+				continue;
+			}
+
+			let relative = entry.path().strip_prefix(game_dir).unwrap()
+				.components()
+				.map(|component| component.as_os_str().to_string_lossy().into_owned())
+				.collect::<Vec<_>>()
+				.join("/");
+			let data = std::fs::read(entry.path())?;
+			zip.start_file(format!("{}/{}", GAME_DIR_ENTRY, relative), options)?;
+			zip.write_all(&data)?;
+			files.push(ArchiveFile {
+				path: relative,
+				sha1: sha1_hex(&data),
+				size: data.len() as u64
+			});
+		}
+	}
+
+	zip.start_file(INSTANCE_ENTRY, options)?;
+	zip.write_all(instance_toml.as_bytes())?;
+	let manifest = ArchiveManifest { format_version: ARCHIVE_FORMAT_VERSION, files };
+	zip.start_file(MANIFEST_ENTRY, options)?;
+	zip.write_all(serde_json::to_string_pretty(&manifest).unwrap().as_bytes())?;
+	zip.finish()?;
+	Ok(())
+}
+
+/// Recreates an instance from an archive written by [`export_instance`]. The archive is checked
+/// against its manifest and nothing is kept if it doesn't match.
+pub async fn import_archive(path: PathBuf) -> McResult<Instance> {
+	let archive_path = path.clone();
+	let (manifest, archived) = tokio::task::spawn_blocking(move || read_archive(&archive_path))
+		.await
+		.map_err(|err| McError::Process(format!("import failed: {}", err)))??;
+
+	let instance = create_instance(archived.name, archived.version, archived.loader, archived.loader_version).await?;
+	let id = instance.id.clone();
+	let imported = async {
+		let target = game_dir(&instance.id);
+		tokio::task::spawn_blocking(move || extract_archive(&path, &manifest, &target))
+			.await
+			.map_err(|err| McError::Process(format!("import failed: {}", err)))??;
+
+		// archives come from other people, so they can't set up commands run on launch
+		let mut settings = archived.settings;
+		strip_local_settings(&mut settings);
+		let instance = Instance {
+			group: archived.group,
+			notes: archived.notes,
+			favourite: archived.favourite,
+			settings,
+			mods: archived.mods,
+			..instance
+		};
+		save_instance(instance.clone()).await?;
+		Ok::<_, McError>(instance)
+	}.await;
+	discard_failed_import(&id, imported).await
+}
+
+/// Removes settings that only make sense on this machine, or that run commands: paths, hooks,
+/// the wrapper, environment changes and JVM arguments that load agents or run commands.
+fn strip_local_settings(settings: &mut InstanceSettings) {
+	settings.java_path = None;
+	settings.game_dir = None;
+	settings.wrapper_command = None;
+	settings.pre_launch_command = None;
+	settings.post_exit_command = None;
+	settings.env.clear();
+	settings.jvm_args = settings.jvm_args.as_ref()
+		.map(|args| args.split_whitespace()
+			.filter(|arg| !UNSAFE_JVM_ARGS.iter().any(|unsafe_arg| arg.starts_with(unsafe_arg)))
+			.collect::<Vec<_>>()
+			.join(" "))
+		.filter(|args| !args.is_empty());
+}
+
+/// Reads the manifest and instance of an archive and checks that it contains exactly the files
+/// the manifest lists.
+fn read_archive(path: &Path) -> McResult<(ArchiveManifest, Instance)> {
+	let mut zip = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+
+	let mut data = String::new();
+	zip.by_name(MANIFEST_ENTRY)
+		.map_err(|_| McError::Integrity(format!("{} is not an instance archive", path.display())))?
+		.read_to_string(&mut data)?;
+	let manifest: ArchiveManifest = serde_json::from_str(&data)
+		.map_err(|err| McError::Integrity(format!("invalid manifest: {}", err)))?;
+	if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+		return Err(McError::Unsupported(format!(
+			"the archive was written by a newer launcher (format {})", manifest.format_version
+		)));
+	}
+
+	if let Some(file) = manifest.files.iter()
+		.find(|file| !Path::new(&file.path).components().all(|component| matches!(component, Component::Normal(_)))) {
+		return Err(McError::Integrity(format!("{} points outside of the game directory", file.path)));
+	}
+	// the same folders export_instance writes, e.g. no options.txt or servers.dat
+	if let Some(file) = manifest.files.iter()
+		.find(|file| !file.path.split_once('/').is_some_and(|(folder, _)| ARCHIVE_FOLDERS.contains(&folder))) {
+		return Err(McError::Integrity(format!("{} is not in a folder an archive can contain", file.path)));
+	}
+
+	data.clear();
+	zip.by_name(INSTANCE_ENTRY)?.read_to_string(&mut data)?;
+	let instance: Instance = toml::from_str(&data)
+		.map_err(|err| McError::Integrity(format!("invalid {}: {}", INSTANCE_ENTRY, err)))?;
+
+	let prefix = format!("{}/", GAME_DIR_ENTRY);
+	let mut listed = 0;
+	for index in 0..zip.len() {
+		let entry = zip.by_index(index)?;
+		let name = entry.name();
+		if entry.is_dir() || name == MANIFEST_ENTRY || name == INSTANCE_ENTRY {
+			continue;
+		}
+		let path = name.strip_prefix(&prefix)
+			.ok_or_else(|| McError::Integrity(format!("unexpected file {} in the archive", name)))?;
+		if !manifest.files.iter().any(|file| file.path == path) {
+			return Err(McError::Integrity(format!("{} is not in the manifest", path)));
+		}
+		listed += 1;
+	}
+	if listed != manifest.files.len() {
+		return Err(McError::Integrity("files of the manifest are missing from the archive".to_string()));
+	}
+	Ok((manifest, instance))
+}
+
+/// Extracts the game files of an archive to `target`, checking each against the manifest.
+fn extract_archive(path: &Path, manifest: &ArchiveManifest, target: &Path) -> McResult<()> {
+	let mut zip = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+	for file in &manifest.files {
+		let entry = zip.by_name(&format!("{}/{}", GAME_DIR_ENTRY, file.path))?;
+		// the manifest isn't trusted yet, a larger entry fails the size check below
+		let mut data = Vec::new();
+		entry.take(file.size + 1).read_to_end(&mut data)?;
+		if data.len() as u64 != file.size || sha1_hex(&data) != file.sha1 {
+			return Err(McError::Integrity(format!("{} doesn't match its hash", file.path)));
+		}
+
+		// read_archive made sure the names don't leave the game directory
+		let path = target.join(&file.path);
+		std::fs::create_dir_all(path.parent().unwrap())?;
+		std::fs::write(path, data)?;
+	}
+	Ok(())
+}
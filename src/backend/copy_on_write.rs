@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+use crate::backend::{McError, McResult};
+
+/// Folders whose files are replaced rather than changed in place, so hard links are safe.
+const HARD_LINK_FOLDERS: &[&str] = &["mods", "resourcepacks", "shaderpacks"];
+/// Smaller files aren't worth linking.
+const LARGE_FILE_SIZE: u64 = 256 * 1024;
+
+/// Copies the directory `from` to `to`, sharing the data of files with the original where the
+/// file system allows it. Top-level entries named in `skip` are left out.
+pub async fn clone_dir(from: PathBuf, to: PathBuf, skip: Vec<String>) -> McResult<()> {
+	let description = from.display().to_string();
+	tokio::task::spawn_blocking(move || clone_dir_blocking(&from, &to, &skip))
+		.await
+		.map_err(|err| McError::Process(format!("copying {} failed: {}", description, err)))?
+}
+
+fn clone_dir_blocking(from: &Path, to: &Path, skip: &[String]) -> McResult<()> {
+	// the folder below the game directory a file is in decides whether it may be hard linked
+	let mut pending: Vec<(PathBuf, PathBuf, Option<String>)> = vec![(from.to_path_buf(), to.to_path_buf(), None)];
+	while let Some((from, to, folder)) = pending.pop() {
+		std::fs::create_dir_all(&to)?;
+		for entry in std::fs::read_dir(&from)? {
+			let entry = entry?;
+			let name = entry.file_name();
+			let target = to.join(&name);
+			if folder.is_none() && skip.iter().any(|skip| name == skip.as_str()) {
+				continue;
+			}
+			let file_type = entry.file_type()?;
+			if file_type.is_dir() {
+				let folder = folder.clone().or_else(|| Some(name.to_string_lossy().into_owned()));
+				pending.push((entry.path(), target, folder));
+				continue;
+			}
+
+			if reflink(&entry.path(), &target).is_ok() {
+				continue;
+			}
+			let linkable = folder.as_deref().is_some_and(|folder| HARD_LINK_FOLDERS.contains(&folder))
+				&& entry.metadata()?.len() >= LARGE_FILE_SIZE;
+			if !linkable || std::fs::hard_link(entry.path(), &target).is_err() {
+				std::fs::copy(entry.path(), &target)?;
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Creates `to` as a copy-on-write clone of `from` (Btrfs, XFS and others).
+#[cfg(target_os = "linux")]
+fn reflink(from: &Path, to: &Path) -> std::io::Result<()> {
+	use std::os::unix::io::AsRawFd;
+	// _IOW(0x94, 9, int) from linux/fs.h
+	const FICLONE: u64 = 0x4004_9409;
+
+	let source = std::fs::File::open(from)?;
+	let target = std::fs::OpenOptions::new().write(true).create_new(true).open(to)?;
+	let result = unsafe { libc::ioctl(target.as_raw_fd(), FICLONE as _, source.as_raw_fd()) };
+	if result == -1 {
+		let err = std::io::Error::last_os_error();
+		drop(target);
+		let _ = std::fs::remove_file(to);
+		return Err(err);
+	}
+	Ok(())
+}
+
+/// Creates `to` as a copy-on-write clone of `from` (APFS).
+#[cfg(target_os = "macos")]
+fn reflink(from: &Path, to: &Path) -> std::io::Result<()> {
+	use std::ffi::CString;
+	use std::os::unix::ffi::OsStrExt;
+
+	let from = CString::new(from.as_os_str().as_bytes())?;
+	let to = CString::new(to.as_os_str().as_bytes())?;
+	if unsafe { libc::clonefile(from.as_ptr(), to.as_ptr(), 0) } == -1 {
+		return Err(std::io::Error::last_os_error());
+	}
+	Ok(())
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn reflink(_from: &Path, _to: &Path) -> std::io::Result<()> {
+	Err(std::io::ErrorKind::Unsupported.into())
+}
@@ -0,0 +1,194 @@
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::backend::{GameProcess, McResult};
+
+#[derive(Debug, Clone)]
+pub struct CrashReport {
+	pub exit_code: Option<i32>,
+	/// Crash report or JVM error log written by the crashed game, if any.
+	pub file: Option<(PathBuf, String)>,
+	pub causes: Vec<String>
+}
+
+/// A known failure, recognized by any of `patterns` appearing in the crash report or game log.
+struct Analyzer {
+	patterns: &'static [&'static str],
+	describe: fn(&str) -> String
+}
+
+const ANALYZERS: &[Analyzer] = &[
+	Analyzer {
+		patterns: &[
+			"java.lang.UnsupportedClassVersionError",
+			"compiled by a more recent version of the Java Runtime"
+		],
+		describe: describe_java_version
+	},
+	Analyzer {
+		patterns: &[
+			"cannot be cast to class java.net.URLClassLoader",
+			"jdk.internal.loader.ClassLoaders$AppClassLoader cannot be cast"
+		],
+		describe: |_| "Wrong Java version: this version of the game only runs on Java 8".to_string()
+	},
+	Analyzer {
+		patterns: &[
+			"java.lang.OutOfMemoryError",
+			"There is insufficient memory for the Java Runtime Environment",
+			"Out of Memory Error"
+		],
+		describe: |_| "Out of memory: the game ran out of memory, try allocating more with -Xmx".to_string()
+	},
+	Analyzer {
+		patterns: &[
+			"Incompatible mods found!",
+			"Incompatible mod set!",
+			"Missing or unsupported mandatory dependencies",
+			"which is missing!"
+		],
+		describe: describe_missing_dependency
+	},
+	Analyzer {
+		patterns: &[
+			"DuplicateModsFoundException",
+			"Found duplicate mods",
+			"Duplicate mods found",
+			"duplicate mod id"
+		],
+		describe: |report| with_details("Duplicate mod: the same mod is installed more than once", report, &["Mod ID", "mod id"])
+	},
+	Analyzer {
+		patterns: &[
+			"org.spongepowered.asm.mixin.transformer.throwables.MixinTransformerError",
+			"org.spongepowered.asm.mixin.injection.throwables.InjectionError",
+			"Mixin apply failed",
+			"Mixin prepare failed"
+		],
+		describe: |report| with_details("Mixin failure: a mod failed to patch the game, it may be incompatible with this version or another mod", report, &["Mixin [", "from mod "])
+	},
+	Analyzer {
+		patterns: &[
+			"Pixel format not accelerated",
+			"The driver does not appear to support OpenGL",
+			"GLFW error 65542",
+			"GLFW error 65543",
+			"Could not create context",
+			"libGL error",
+			"atio6axx.dll",
+			"ig9icd64.dll",
+			"ig75icd64.dll",
+			"nvoglv64.dll",
+			"libnvidia-glcore.so"
+		],
+		describe: |_| "Graphics driver problem: OpenGL could not be initialized or the driver crashed, try updating the graphics drivers".to_string()
+	}
+];
+
+/// Runs every known analyzer over `text` and returns a human readable probable cause for each match.
+pub fn analyze_crash(text: &str) -> Vec<String> {
+	ANALYZERS.iter()
+		.filter(|analyzer| analyzer.patterns.iter().any(|pattern| text.contains(pattern)))
+		.map(|analyzer| (analyzer.describe)(text))
+		.collect()
+}
+
+/// Waits for `game` to exit and returns a crash report if it exited abnormally without being stopped.
+pub async fn wait_for_crash(game: &GameProcess) -> McResult<Option<CrashReport>> {
+	let code = game.wait().await;
+	if code == Some(0) || game.stop_requested() {
+		return Ok(None);
+	}
+	let report = find_crash_report(&game.game_dir, game.started_at, &game.log_file, code).await?;
+	Ok(Some(report))
+}
+
+/// Looks for a crash report in `<game_dir>/crash-reports` or a JVM error log `hs_err_pid*.log` in
+/// `game_dir` written after `since`, analyzes it together with the game log and returns the result.
+pub async fn find_crash_report(game_dir: &Path, since: SystemTime, game_log: &Path, exit_code: Option<i32>) -> McResult<CrashReport> {
+	let report = newest_file(&game_dir.join("crash-reports"), since, |name| name.ends_with(".txt")).await?;
+	let jvm_log = newest_file(game_dir, since, |name| name.starts_with("hs_err_pid") && name.ends_with(".log")).await?;
+	let newest = match (report, jvm_log) {
+		(Some(report), Some(jvm_log)) => Some(if report.0 >= jvm_log.0 { report } else { jvm_log }),
+		(report, jvm_log) => report.or(jvm_log)
+	};
+
+	let file = match newest {
+		Some((_, path)) => {
+			let contents = String::from_utf8_lossy(&tokio::fs::read(&path).await?).into_owned();
+			Some((path, contents))
+		}
+		None => None
+	};
+
+	let mut text = tokio::fs::read_to_string(game_log).await.unwrap_or_default();
+	if let Some((_, contents)) = &file {
+		text.push('\n');
+		text.push_str(contents);
+	}
+
+	Ok(CrashReport {
+		exit_code,
+		file,
+		causes: analyze_crash(&text)
+	})
+}
+
+async fn newest_file(dir: &Path, since: SystemTime, matches: fn(&str) -> bool) -> McResult<Option<(SystemTime, PathBuf)>> {
+	let mut entries = match tokio::fs::read_dir(dir).await {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(err.into())
+	};
+
+	let mut newest: Option<(SystemTime, PathBuf)> = None;
+	while let Some(entry) = entries.next_entry().await? {
+		if !entry.file_name().to_str().is_some_and(matches) {
+			continue;
+		}
+		let modified = entry.metadata().await?.modified()?;
+		if modified >= since && newest.as_ref().map_or(true, |(time, _)| modified > *time) {
+			newest = Some((modified, entry.path()));
+		}
+	}
+	Ok(newest)
+}
+
+fn describe_java_version(report: &str) -> String {
+	let required = report.find("class file version ")
+		.map(|start| &report[start + "class file version ".len()..])
+		.and_then(|rest| rest.split(|c: char| !c.is_ascii_digit()).next())
+		.and_then(|version| version.parse::<u32>().ok())
+		.map(|class_version| class_version.saturating_sub(44));
+	match required {
+		Some(java) => format!("Wrong Java version: the game or a mod requires at least Java {}", java),
+		None => "Wrong Java version: the game or a mod was compiled for a newer Java version".to_string()
+	}
+}
+
+fn describe_missing_dependency(report: &str) -> String {
+	with_details(
+		"Missing mod dependency: a mod requires another mod or a different version of it",
+		report,
+		&["requires", "Mod ID:", "which is missing"]
+	)
+}
+
+/// Appends the first few distinct lines of `report` containing any of `markers` to `summary`.
+fn with_details(summary: &str, report: &str, markers: &[&str]) -> String {
+	let mut details: Vec<&str> = Vec::new();
+	for line in report.lines().map(str::trim) {
+		if details.len() >= 5 {
+			break;
+		}
+		if markers.iter().any(|marker| line.contains(marker)) && !details.contains(&line) {
+			details.push(line);
+		}
+	}
+
+	let mut text = summary.to_string();
+	for line in details {
+		text.push_str("\n  ");
+		text.push_str(line);
+	}
+	text
+}
@@ -0,0 +1,193 @@
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use crate::model::LauncherConfig;
+
+const APP_NAME: &str = "hzlauncher";
+/// Overrides the data directory, takes precedence over the config file.
+pub const DATA_DIR_ENV: &str = "HZLAUNCHER_DATA_DIR";
+/// Overrides the cache directory, takes precedence over the config file.
+pub const CACHE_DIR_ENV: &str = "HZLAUNCHER_CACHE_DIR";
+pub const DATA_DIR_FLAG: &str = "--data-dir";
+pub const CACHE_DIR_FLAG: &str = "--cache-dir";
+const CONFIG_FILE: &str = "launcher.toml";
+/// Where older versions kept everything, relative to the working directory.
+const LEGACY_DATA_DIR: &str = "data";
+/// Entries of the legacy data directory that belong in the cache directory.
+const CACHE_ENTRIES: &[&str] = &["versions.json", "versions", "clients", "libraries", "assets", "natives", "loaders"];
+/// Entries only a data directory of the launcher has, any other `data` directory is left alone.
+const LEGACY_MARKERS: &[&str] = &["account.toml", "versions.json", "instance"];
+
+static DIRS: OnceLock<LauncherDirs> = OnceLock::new();
+
+/// Where the launcher keeps its files, all paths are absolute.
+#[derive(Debug, Clone)]
+pub struct LauncherDirs {
+	/// Accounts, instances and logs.
+	pub data: PathBuf,
+	/// Versions, libraries and assets, which can all be downloaded again.
+	pub cache: PathBuf,
+	/// Contains `launcher.toml`.
+	pub config: PathBuf
+}
+
+/// Directories given on the command line.
+#[derive(Debug, Clone, Default)]
+pub struct DirOverrides {
+	pub data: Option<PathBuf>,
+	pub cache: Option<PathBuf>
+}
+
+/// Resolves the launcher directories, which can't change afterwards. Command line overrides
+/// come first, then the environment, the config file and finally the platform defaults (the XDG
+/// base directories on Linux).
+pub fn init_dirs(overrides: DirOverrides) -> &'static LauncherDirs {
+	DIRS.get_or_init(|| resolve_dirs(overrides))
+}
+
+/// The launcher directories, resolved without command line overrides if [`init_dirs`] wasn't
+/// called.
+pub fn launcher_dirs() -> &'static LauncherDirs {
+	init_dirs(DirOverrides::default())
+}
+
+pub fn data_dir() -> &'static Path {
+	&launcher_dirs().data
+}
+
+pub fn cache_dir() -> &'static Path {
+	&launcher_dirs().cache
+}
+
+fn resolve_dirs(overrides: DirOverrides) -> LauncherDirs {
+	let legacy = absolute(Path::new(LEGACY_DATA_DIR));
+	let defaults = default_dirs().unwrap_or_else(|| LauncherDirs {
+		data: legacy.clone(),
+		cache: legacy.clone(),
+		config: legacy
+	});
+	let config = load_config(&defaults.config);
+
+	let env_dir = |var: &str| std::env::var_os(var).filter(|dir| !dir.is_empty()).map(PathBuf::from);
+	let data = overrides.data.map(|dir| absolute(&dir))
+		.or_else(|| env_dir(DATA_DIR_ENV).map(|dir| absolute(&dir)))
+		.or_else(|| config.data_dir.map(|dir| defaults.config.join(dir)));
+	let cache = overrides.cache.map(|dir| absolute(&dir))
+		.or_else(|| env_dir(CACHE_DIR_ENV).map(|dir| absolute(&dir)))
+		.or_else(|| config.cache_dir.map(|dir| defaults.config.join(dir)));
+
+	LauncherDirs {
+		// a custom data directory keeps everything in one place unless told otherwise
+		cache: cache.unwrap_or_else(|| match &data {
+			Some(data) => data.join("cache"),
+			None => defaults.cache
+		}),
+		data: data.unwrap_or(defaults.data),
+		config: defaults.config
+	}
+}
+
+fn default_dirs() -> Option<LauncherDirs> {
+	if cfg!(windows) {
+		let roaming = Path::new(&std::env::var_os("APPDATA")?).join(APP_NAME);
+		let local = std::env::var_os("LOCALAPPDATA")
+			.map_or_else(|| roaming.clone(), |dir| Path::new(&dir).join(APP_NAME));
+		Some(LauncherDirs { data: roaming.clone(), cache: local.join("cache"), config: roaming })
+	} else if cfg!(target_os = "macos") {
+		let home = PathBuf::from(std::env::var_os("HOME")?);
+		let support = home.join("Library/Application Support").join(APP_NAME);
+		Some(LauncherDirs { data: support.clone(), cache: home.join("Library/Caches").join(APP_NAME), config: support })
+	} else {
+		let home = PathBuf::from(std::env::var_os("HOME")?);
+		// the spec says relative paths are invalid and should be ignored
+		let xdg_dir = |var: &str, default: &str| std::env::var_os(var)
+			.map(PathBuf::from)
+			.filter(|dir| dir.is_absolute())
+			.unwrap_or_else(|| home.join(default))
+			.join(APP_NAME);
+		Some(LauncherDirs {
+			data: xdg_dir("XDG_DATA_HOME", ".local/share"),
+			cache: xdg_dir("XDG_CACHE_HOME", ".cache"),
+			config: xdg_dir("XDG_CONFIG_HOME", ".config")
+		})
+	}
+}
+
+fn load_config(config_dir: &Path) -> LauncherConfig {
+	let path = config_dir.join(CONFIG_FILE);
+	match std::fs::read_to_string(&path) {
+		Ok(data) => toml::from_str(&data).unwrap_or_else(|err| {
+			eprintln!("error: failed to parse {}: {}", path.display(), err);
+			LauncherConfig::default()
+		}),
+		Err(_) => LauncherConfig::default()
+	}
+}
+
+fn absolute(path: &Path) -> PathBuf {
+	std::env::current_dir().map_or_else(|_| path.to_path_buf(), |dir| dir.join(path))
+}
+
+/// Moves a `data` directory in the working directory left by older versions into the resolved
+/// directories, if it has files only the launcher writes. Entries that already exist in the new
+/// location are left where they are. Returns the names of the moved entries.
+pub fn migrate_legacy_data() -> std::io::Result<Vec<String>> {
+	let dirs = launcher_dirs();
+	let legacy = match Path::new(LEGACY_DATA_DIR).canonicalize() {
+		Ok(legacy) if legacy.is_dir() => legacy,
+		_ => return Ok(Vec::new())
+	};
+	// launched from a desktop entry the working directory is usually the home directory
+	if !LEGACY_MARKERS.iter().any(|marker| legacy.join(marker).exists()) {
+		return Ok(Vec::new());
+	}
+	// nothing to do when the launcher is configured to use it, or a directory inside it
+	let in_legacy = |dir: &Path| dir.canonicalize().is_ok_and(|dir| dir.starts_with(&legacy));
+	if in_legacy(&dirs.data) || in_legacy(&dirs.cache) {
+		return Ok(Vec::new());
+	}
+
+	let mut moved = Vec::new();
+	for entry in std::fs::read_dir(&legacy)? {
+		let entry = entry?;
+		let name = entry.file_name();
+		let name = name.to_string_lossy();
+		let target_dir = if CACHE_ENTRIES.contains(&&*name) { &dirs.cache } else { &dirs.data };
+		let target = target_dir.join(entry.file_name());
+		if target.exists() {
+			eprintln!("error: not migrating {}, {} already exists", entry.path().display(), target.display());
+			continue;
+		}
+		std::fs::create_dir_all(target_dir)?;
+		move_entry(&entry.path(), &target)?;
+		moved.push(name.into_owned());
+	}
+	// only removed when everything was moved
+	let _ = std::fs::remove_dir(&legacy);
+	Ok(moved)
+}
+
+/// Renames `from` to `to`, copying it when they are on different file systems.
+fn move_entry(from: &Path, to: &Path) -> std::io::Result<()> {
+	if std::fs::rename(from, to).is_ok() {
+		return Ok(());
+	}
+	if !from.is_dir() {
+		std::fs::copy(from, to)?;
+		return std::fs::remove_file(from);
+	}
+
+	let mut pending = vec![(from.to_path_buf(), to.to_path_buf())];
+	while let Some((from, to)) = pending.pop() {
+		std::fs::create_dir_all(&to)?;
+		for entry in std::fs::read_dir(&from)? {
+			let entry = entry?;
+			let target = to.join(entry.file_name());
+			if entry.file_type()?.is_dir() {
+				pending.push((entry.path(), target));
+			} else {
+				std::fs::copy(entry.path(), target)?;
+			}
+		}
+	}
+	std::fs::remove_dir_all(from)
+}
@@ -0,0 +1,535 @@
+use std::fmt::{Debug, Formatter};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::sync::{Arc, Mutex};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, SystemTime};
+use time::macros::format_description;
+use tokio::io::{AsyncBufReadExt, AsyncRead, AsyncWriteExt, BufReader};
+use tokio::process::Child;
+use tokio::sync::{broadcast, mpsc, Notify, watch};
+use crate::backend::{LaunchCommand, McError, McResult};
+
+/// Amount of lines the console keeps per game, older lines are only available from the log file.
+pub const MAX_LINES: usize = 20000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameState {
+	/// The process was spawned but hasn't produced any output yet.
+	Starting,
+	Running,
+	/// The process exited, the code is `None` if it was killed by a signal.
+	Exited(Option<i32>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LogLevel {
+	Trace,
+	Debug,
+	Info,
+	Warn,
+	Error,
+	Fatal
+}
+
+impl LogLevel {
+	pub const ALL: [LogLevel; 6] = [
+		LogLevel::Trace,
+		LogLevel::Debug,
+		LogLevel::Info,
+		LogLevel::Warn,
+		LogLevel::Error,
+		LogLevel::Fatal
+	];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			LogLevel::Trace => "TRACE",
+			LogLevel::Debug => "DEBUG",
+			LogLevel::Info => "INFO",
+			LogLevel::Warn => "WARN",
+			LogLevel::Error => "ERROR",
+			LogLevel::Fatal => "FATAL"
+		}
+	}
+
+	fn parse(level: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|l| l.as_str().eq_ignore_ascii_case(level))
+	}
+
+	/// Guesses the level of a plain text line such as `[12:00:00] [Render thread/WARN]: ...`.
+	fn guess(line: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|l| {
+			line.contains(&format!("/{}]", l.as_str())) || line.contains(&format!("[{}]", l.as_str()))
+		})
+	}
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogStream {
+	Stdout,
+	Stderr
+}
+
+#[derive(Debug, Clone)]
+pub struct LogLine {
+	pub level: Option<LogLevel>,
+	pub thread: Option<String>,
+	pub message: String
+}
+
+impl LogLine {
+	fn plain(message: String) -> Self {
+		Self {
+			level: LogLevel::guess(&message),
+			thread: None,
+			message
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub enum GameEvent {
+	Started {
+		id: usize,
+		instance_id: String,
+		version: String,
+		log_file: PathBuf
+	},
+	Line(usize, LogLine),
+	State(usize, GameState)
+}
+
+/// A running (or finished) game process. Output is captured line by line, written to a per
+/// launch log file and published to the subscribers of the events channel it was spawned with.
+pub struct GameProcess {
+	pub id: usize,
+	pub instance_id: String,
+	pub version: String,
+	pub game_dir: PathBuf,
+	pub log_file: PathBuf,
+	pub pid: Option<u32>,
+	pub started_at: SystemTime,
+	ended_at: Mutex<Option<SystemTime>>,
+	/// Signal that killed the process, only known on unix.
+	exit_signal: Mutex<Option<i32>>,
+	state: watch::Sender<GameState>,
+	kill: Notify,
+	stop_requested: AtomicBool,
+	events: broadcast::Sender<GameEvent>,
+	/// Copy of the launch command, only used to redact its secrets from the output.
+	launch: LaunchCommand
+}
+
+impl Debug for GameProcess {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		f.debug_struct("GameProcess")
+			.field("id", &self.id)
+			.field("version", &self.version)
+			.field("pid", &self.pid)
+			.field("state", &self.state())
+			.finish()
+	}
+}
+
+impl GameProcess {
+	pub async fn spawn(
+		id: usize,
+		launch: &LaunchCommand,
+		log_dir: &Path,
+		events: broadcast::Sender<GameEvent>) -> McResult<Arc<Self>> {
+		tokio::fs::create_dir_all(log_dir).await?;
+		let now = time::OffsetDateTime::now_utc();
+		let name = now.format(format_description!("[year]-[month]-[day]_[hour]-[minute]-[second]")).unwrap();
+		let log_file = log_dir.join(format!("{}-{}.log", name, id));
+		let file = tokio::fs::File::create(&log_file).await?;
+
+		// the access token would be readable by everyone through the process list otherwise
+		let argfile = match java_major_version(&launch.program).await {
+			Some(version) if version >= 9 => {
+				let argfile = log_dir.join(format!("{}-{}.args", name, id));
+				write_private(&argfile, launch.argfile_contents().as_bytes()).await?;
+				Some(argfile)
+			}
+			_ => None
+		};
+
+		let mut command = launch.to_command(argfile.as_deref());
+		command
+			.stdin(Stdio::null())
+			.stdout(Stdio::piped())
+			.stderr(Stdio::piped());
+		// own process group, so stopping the game also stops anything it started
+		#[cfg(unix)]
+		command.process_group(0);
+		let mut child = match command.spawn() {
+			Ok(child) => child,
+			Err(err) => {
+				if let Some(argfile) = &argfile {
+					tokio::fs::remove_file(argfile).await.ok();
+				}
+				return Err(McError::Process(format!("failed to start the game: {}", err)));
+			}
+		};
+
+		let (line_sender, line_receiver) = mpsc::unbounded_channel();
+		tokio::spawn(read_lines(child.stdout.take().unwrap(), LogStream::Stdout, line_sender.clone()));
+		tokio::spawn(read_lines(child.stderr.take().unwrap(), LogStream::Stderr, line_sender));
+
+		let (state, _) = watch::channel(GameState::Starting);
+		let game = Arc::new(Self {
+			id,
+			instance_id: launch.instance_id.clone(),
+			version: launch.version.clone(),
+			game_dir: launch.working_dir.clone(),
+			log_file,
+			pid: child.id(),
+			started_at: SystemTime::now(),
+			ended_at: Mutex::new(None),
+			exit_signal: Mutex::new(None),
+			state,
+			kill: Notify::new(),
+			stop_requested: AtomicBool::new(false),
+			events,
+			launch: launch.redacted()
+		});
+		game.events.send(GameEvent::Started {
+			id,
+			instance_id: game.instance_id.clone(),
+			version: game.version.clone(),
+			log_file: game.log_file.clone()
+		}).ok();
+		tokio::spawn(game.clone().supervise(child, line_receiver, file, argfile));
+		Ok(game)
+	}
+
+	pub fn state(&self) -> GameState {
+		*self.state.borrow()
+	}
+
+	/// Waits for the process to exit and returns its exit code.
+	pub async fn wait(&self) -> Option<i32> {
+		let mut receiver = self.state.subscribe();
+		let state = *receiver.wait_for(|state| matches!(state, GameState::Exited(_))).await.unwrap();
+		match state {
+			GameState::Exited(code) => code,
+			_ => unreachable!()
+		}
+	}
+
+	/// The signal that killed the process, if it was killed by one.
+	pub fn exit_signal(&self) -> Option<i32> {
+		*self.exit_signal.lock().unwrap()
+	}
+
+	/// Time since the game was started, or how long it ran if it already exited.
+	pub fn uptime(&self) -> Duration {
+		let end = self.ended_at.lock().unwrap().unwrap_or_else(SystemTime::now);
+		end.duration_since(self.started_at).unwrap_or_default()
+	}
+
+	/// Asks the game to exit and kills its whole process group if it's still running after
+	/// `timeout`. Returns once the game has exited.
+	pub async fn stop(&self, timeout: Duration) {
+		if matches!(self.state(), GameState::Exited(_)) {
+			return;
+		}
+		self.stop_requested.store(true, Ordering::Relaxed);
+
+		#[cfg(unix)]
+		if let Some(pid) = self.pid {
+			// the game is the leader of its own process group, signal the whole group
+			unsafe {
+				libc::kill(-(pid as libc::pid_t), libc::SIGTERM);
+			}
+			if tokio::time::timeout(timeout, self.wait()).await.is_ok() {
+				return;
+			}
+			unsafe {
+				libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+			}
+		}
+		#[cfg(not(unix))]
+		let _ = timeout;
+
+		self.kill.notify_one();
+		self.wait().await;
+	}
+
+	/// Whether the game was stopped through [`GameProcess::stop`] rather than exiting on its own.
+	pub fn stop_requested(&self) -> bool {
+		self.stop_requested.load(Ordering::Relaxed)
+	}
+
+	fn set_state(&self, state: GameState) {
+		self.state.send_replace(state);
+		self.events.send(GameEvent::State(self.id, state)).ok();
+	}
+
+	fn publish_line(&self, line: LogLine) {
+		if self.state() == GameState::Starting {
+			self.set_state(GameState::Running);
+		}
+		self.events.send(GameEvent::Line(self.id, line)).ok();
+	}
+
+	async fn supervise(
+		self: Arc<Self>,
+		mut child: Child,
+		mut line_receiver: mpsc::UnboundedReceiver<(LogStream, String)>,
+		mut file: tokio::fs::File,
+		argfile: Option<PathBuf>) {
+		let process_lines = async {
+			let mut parser = XmlEventParser::default();
+			while let Some((stream, raw)) = line_receiver.recv().await {
+				let raw = self.launch.redact(&raw);
+				if let Err(err) = file.write_all(format!("{}\n", raw).as_bytes()).await {
+					eprintln!("error: failed to write game log {}: {}", self.log_file.display(), err);
+				}
+
+				let line = match stream {
+					LogStream::Stdout => match parser.push(raw) {
+						Some(line) => line,
+						None => continue
+					},
+					LogStream::Stderr => LogLine::plain(raw)
+				};
+				self.publish_line(line);
+			}
+			// the game may have died in the middle of an event, its last lines matter most
+			if let Some(line) = parser.finish() {
+				self.publish_line(line);
+			}
+		};
+
+		let wait_child = async {
+			let status = tokio::select! {
+				status = child.wait() => status,
+				_ = self.kill.notified() => {
+					child.start_kill().ok();
+					child.wait().await
+				}
+			};
+			match status {
+				Ok(status) => {
+					#[cfg(unix)]
+					{
+						use std::os::unix::process::ExitStatusExt;
+						*self.exit_signal.lock().unwrap() = status.signal();
+					}
+					status.code()
+				}
+				Err(err) => {
+					eprintln!("error: failed to wait for the game process: {}", err);
+					None
+				}
+			}
+		};
+
+		let ((), code) = tokio::join!(process_lines, wait_child);
+		// a wrapper command may print before it starts the JVM, so only the exit tells for sure
+		// that the arguments were read
+		if let Some(path) = &argfile {
+			remove_argfile(path).await;
+		}
+		*self.ended_at.lock().unwrap() = Some(SystemTime::now());
+		let exit_line = match code {
+			Some(code) => format!("[HZLauncher] Process exited with code {}\n", code),
+			None => match self.exit_signal() {
+				Some(signal) => format!("[HZLauncher] Process was terminated by signal {}\n", signal),
+				None => "[HZLauncher] Process was terminated by a signal\n".to_string()
+			}
+		};
+		file.write_all(exit_line.as_bytes()).await.ok();
+		file.flush().await.ok();
+		self.set_state(GameState::Exited(code));
+	}
+}
+
+/// Returns the major version of the Java runtime `program`, e.g. 8 for `1.8.0_392` or 17 for `17.0.9`.
+pub async fn java_major_version(program: &str) -> Option<u32> {
+	let output = tokio::process::Command::new(program)
+		.arg("-version")
+		.stdin(Stdio::null())
+		.output()
+		.await
+		.ok()?;
+	// the version is printed to stderr, e.g. `openjdk version "17.0.9" 2023-10-17`
+	let text = String::from_utf8_lossy(&output.stderr);
+	let start = text.find("version \"")? + "version \"".len();
+	let version = &text[start..];
+	let version = &version[..version.find('"')?];
+	let mut parts = version.split(|c: char| !c.is_ascii_digit());
+	match parts.next()?.parse().ok()? {
+		1 => parts.next()?.parse().ok(),
+		major => Some(major)
+	}
+}
+
+/// Writes `contents` to a file only the current user can read.
+async fn write_private(path: &Path, contents: &[u8]) -> McResult<()> {
+	let mut options = tokio::fs::OpenOptions::new();
+	options.write(true).create(true).truncate(true);
+	#[cfg(unix)]
+	options.mode(0o600);
+	let mut file = options.open(path).await?;
+	file.write_all(contents).await?;
+	file.flush().await?;
+	Ok(())
+}
+
+async fn remove_argfile(path: &Path) {
+	if let Err(err) = tokio::fs::remove_file(path).await {
+		if err.kind() != tokio::io::ErrorKind::NotFound {
+			eprintln!("error: failed to remove argument file {}: {}", path.display(), err);
+		}
+	}
+}
+
+/// Parses the contents of a log file written by [`GameProcess`].
+pub fn parse_log(contents: &str) -> Vec<LogLine> {
+	let mut parser = XmlEventParser::default();
+	let mut lines: Vec<LogLine> = contents.lines().filter_map(|line| parser.push(line.to_string())).collect();
+	lines.extend(parser.finish());
+	lines
+}
+
+/// Returns the path and contents of the newest log in `log_dir` other than `exclude`.
+pub async fn previous_game_log(log_dir: &Path, exclude: Option<&Path>) -> McResult<Option<(PathBuf, String)>> {
+	let mut entries = match tokio::fs::read_dir(log_dir).await {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => return Ok(None),
+		Err(err) => return Err(McError::from(err))
+	};
+
+	let mut newest: Option<PathBuf> = None;
+	while let Some(entry) = entries.next_entry().await? {
+		let path = entry.path();
+		if exclude.is_some_and(|exclude| exclude == path.as_path()) || path.extension().map_or(true, |ext| ext != "log") {
+			continue;
+		}
+		// the file names start with the launch time, so the newest log sorts last
+		if newest.as_ref().map_or(true, |newest| path.file_name() > newest.file_name()) {
+			newest = Some(path);
+		}
+	}
+
+	match newest {
+		Some(path) => {
+			let contents = tokio::fs::read_to_string(&path).await?;
+			Ok(Some((path, contents)))
+		}
+		None => Ok(None)
+	}
+}
+
+async fn read_lines<R: AsyncRead + Unpin>(reader: R, stream: LogStream, sender: mpsc::UnboundedSender<(LogStream, String)>) {
+	let mut lines = BufReader::new(reader).lines();
+	loop {
+		match lines.next_line().await {
+			Ok(Some(line)) => {
+				if sender.send((stream, line)).is_err() {
+					break;
+				}
+			}
+			Ok(None) => break,
+			Err(err) => {
+				eprintln!("error: failed to read game output: {}", err);
+				break;
+			}
+		}
+	}
+}
+
+/// Reassembles log4j XML events, both the multi line `log4j:Event` format used by Mojang's
+/// configurations and the single line `Event` format of log4j2's `XMLLayout`.
+#[derive(Default)]
+struct XmlEventParser {
+	buffer: Option<String>
+}
+
+impl XmlEventParser {
+	fn push(&mut self, line: String) -> Option<LogLine> {
+		if let Some(buffer) = &mut self.buffer {
+			buffer.push('\n');
+			buffer.push_str(&line);
+			if is_event_end(&line) {
+				let event = self.buffer.take().unwrap();
+				return Some(parse_event(&event).unwrap_or_else(|| LogLine::plain(event)));
+			}
+			return None;
+		}
+
+		let trimmed = line.trim_start();
+		if trimmed.starts_with("<log4j:Event") || trimmed.starts_with("<Event ") {
+			if is_event_end(&line) {
+				return Some(parse_event(&line).unwrap_or_else(|| LogLine::plain(line)));
+			}
+			self.buffer = Some(line);
+			None
+		} else {
+			Some(LogLine::plain(line))
+		}
+	}
+
+	/// Returns an event that never ended as plain text.
+	fn finish(&mut self) -> Option<LogLine> {
+		self.buffer.take().map(LogLine::plain)
+	}
+}
+
+fn is_event_end(line: &str) -> bool {
+	line.contains("</log4j:Event>") || line.contains("</Event>")
+}
+
+fn parse_event(xml: &str) -> Option<LogLine> {
+	let start = xml.find('<')?;
+	let tag_end = start + xml[start..].find('>')?;
+	let tag = &xml[start..tag_end];
+
+	let level = attribute(tag, "level").and_then(|level| LogLevel::parse(&level));
+	let thread = attribute(tag, "thread");
+
+	let mut message = element(xml, "log4j:Message").or_else(|| element(xml, "Message")).unwrap_or_default();
+	if let Some(throwable) = element(xml, "log4j:Throwable").or_else(|| element(xml, "Thrown")) {
+		message.push('\n');
+		message.push_str(&throwable);
+	}
+
+	Some(LogLine {
+		level,
+		thread,
+		message
+	})
+}
+
+fn attribute(tag: &str, name: &str) -> Option<String> {
+	let pattern = format!(" {}=\"", name);
+	let start = tag.find(&pattern)? + pattern.len();
+	let end = start + tag[start..].find('"')?;
+	Some(unescape(&tag[start..end]))
+}
+
+fn element(xml: &str, name: &str) -> Option<String> {
+	let open = format!("<{}>", name);
+	let close = format!("</{}>", name);
+	let start = xml.find(&open)? + open.len();
+	let end = start + xml[start..].find(&close)?;
+	let content = xml[start..end].trim();
+	if let Some(data) = content.strip_prefix("<![CDATA[").and_then(|c| c.strip_suffix("]]>")) {
+		Some(data.to_string())
+	} else {
+		Some(unescape(content))
+	}
+}
+
+fn unescape(value: &str) -> String {
+	value
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&#xD;", "\r")
+		.replace("&#xA;", "\n")
+		.replace("&amp;", "&")
+}
@@ -0,0 +1,113 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use time::format_description::well_known::Rfc3339;
+use time::OffsetDateTime;
+use tokio::sync::Mutex;
+use crate::backend::{McError, McResult};
+use crate::model::LaunchRecord;
+
+const HISTORY_FILE: &str = "history.json";
+const EXPORTS_DIR: &str = "exports";
+
+/// Serializes the read-modify-write cycles of the history files.
+static HISTORY_LOCK: Mutex<()> = Mutex::const_new(());
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HistoryFormat {
+	Csv,
+	Json
+}
+
+pub fn unix_millis(time: SystemTime) -> u64 {
+	time.duration_since(UNIX_EPOCH).map_or(0, |duration| duration.as_millis() as u64)
+}
+
+/// Formats milliseconds since the unix epoch as an RFC 3339 UTC timestamp.
+pub fn format_unix_millis(millis: u64) -> String {
+	OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+		.ok()
+		.and_then(|time| time.format(&Rfc3339).ok())
+		.unwrap_or_default()
+}
+
+/// Reads the launch history of the instance in `dir`, oldest first.
+pub async fn load_history(dir: &Path) -> McResult<Vec<LaunchRecord>> {
+	let path = dir.join(HISTORY_FILE);
+	match tokio::fs::read_to_string(&path).await {
+		Ok(data) => serde_json::from_str(&data)
+			.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err))),
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => Ok(Vec::new()),
+		Err(err) => Err(McError::from(err))
+	}
+}
+
+async fn save_history(dir: &Path, history: &[LaunchRecord]) -> McResult<()> {
+	tokio::fs::create_dir_all(dir).await?;
+	tokio::fs::write(dir.join(HISTORY_FILE), serde_json::to_string_pretty(history).unwrap()).await?;
+	Ok(())
+}
+
+/// Adds a launch that just started to the history.
+pub async fn record_launch(dir: &Path, record: LaunchRecord) -> McResult<()> {
+	let _guard = HISTORY_LOCK.lock().await;
+	let mut history = load_history(dir).await?;
+	history.push(record);
+	save_history(dir, &history).await
+}
+
+/// Stores the end time and exit code of the unfinished launch started at `started_at`.
+pub async fn finish_launch(dir: &Path, started_at: u64, ended_at: u64, exit_code: Option<i32>) -> McResult<()> {
+	let _guard = HISTORY_LOCK.lock().await;
+	let mut history = load_history(dir).await?;
+	let record = history.iter_mut()
+		.rev()
+		.find(|record| record.started_at == started_at && record.ended_at.is_none());
+	if let Some(record) = record {
+		record.ended_at = Some(ended_at);
+		record.exit_code = exit_code;
+		save_history(dir, &history).await?;
+	}
+	Ok(())
+}
+
+/// Writes the history of the instance in `dir` to `<dir>/exports` and returns the path
+/// of the written file.
+pub async fn export_history(dir: &Path, format: HistoryFormat) -> McResult<PathBuf> {
+	let history = load_history(dir).await?;
+	let (extension, data) = match format {
+		HistoryFormat::Csv => ("csv", history_csv(&history)),
+		HistoryFormat::Json => ("json", serde_json::to_string_pretty(&history).unwrap())
+	};
+
+	let dir = dir.join(EXPORTS_DIR);
+	tokio::fs::create_dir_all(&dir).await?;
+	let path = dir.join(format!("history-{}.{}", unix_millis(SystemTime::now()) / 1000, extension));
+	tokio::fs::write(&path, data).await?;
+	Ok(path)
+}
+
+fn history_csv(history: &[LaunchRecord]) -> String {
+	let mut csv = "started_at,ended_at,playtime_seconds,exit_code,account,version,loader\n".to_string();
+	for record in history {
+		let fields = [
+			format_unix_millis(record.started_at),
+			record.ended_at.map(format_unix_millis).unwrap_or_default(),
+			record.playtime().as_secs().to_string(),
+			record.exit_code.map(|code| code.to_string()).unwrap_or_default(),
+			record.account.clone(),
+			record.version.clone(),
+			record.loader.clone()
+		];
+		csv.push_str(&fields.iter().map(|field| csv_field(field)).collect::<Vec<_>>().join(","));
+		csv.push('\n');
+	}
+	csv
+}
+
+fn csv_field(field: &str) -> String {
+	if field.contains([',', '"', '\n', '\r']) {
+		format!("\"{}\"", field.replace('"', "\"\""))
+	} else {
+		field.to_string()
+	}
+}
@@ -0,0 +1,118 @@
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use crate::backend::{instance_dir, instance_game_dir, load_instance, McError, McResult, save_instance, unix_millis};
+use crate::model::Instance;
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Path of the icon of an instance, if it has one.
+pub fn instance_icon(instance: &Instance) -> Option<PathBuf> {
+	instance.icon.as_ref().map(|icon| instance_dir(&instance.id).join(icon))
+}
+
+/// Makes the PNG image at `path` the icon of an instance and returns the updated instance.
+pub async fn import_instance_icon(id: String, path: PathBuf) -> McResult<Instance> {
+	let data = tokio::fs::read(&path).await?;
+	set_instance_icon(id, Some(data)).await
+}
+
+/// Makes the icon of a mod in the `mods` folder of an instance its icon.
+pub async fn use_mod_icon(id: String, file: String) -> McResult<Instance> {
+	let instance = load_instance(&id).await?;
+	let path = instance_game_dir(&instance).join("mods").join(file);
+	let data = tokio::task::spawn_blocking(move || read_mod_icon(&path))
+		.await
+		.map_err(|err| McError::Process(format!("failed to read the mod: {}", err)))??;
+	set_instance_icon(id, Some(data)).await
+}
+
+pub async fn remove_instance_icon(id: String) -> McResult<Instance> {
+	set_instance_icon(id, None).await
+}
+
+/// Lists the mod jars of an instance whose icon can be used.
+pub async fn list_mod_files(instance: &Instance) -> McResult<Vec<String>> {
+	let mut entries = match tokio::fs::read_dir(instance_game_dir(instance).join("mods")).await {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(err) => return Err(McError::from(err))
+	};
+	let mut files = Vec::new();
+	while let Some(entry) = entries.next_entry().await? {
+		if let Some(name) = entry.file_name().to_str().filter(|name| name.ends_with(".jar")) {
+			files.push(name.to_string());
+		}
+	}
+	files.sort();
+	Ok(files)
+}
+
+/// Replaces the icon file of an instance. Every icon gets a new name, so the UI doesn't show a
+/// cached image of the old one.
+async fn set_instance_icon(id: String, data: Option<Vec<u8>>) -> McResult<Instance> {
+	if data.as_ref().is_some_and(|data| !data.starts_with(PNG_SIGNATURE)) {
+		return Err(McError::Unsupported("icons have to be PNG images".to_string()));
+	}
+	let mut instance = load_instance(&id).await?;
+	if let Some(old) = instance_icon(&instance) {
+		if let Err(err) = tokio::fs::remove_file(&old).await {
+			eprintln!("error: failed to remove the old icon {}: {}", old.display(), err);
+		}
+	}
+
+	instance.icon = match data {
+		Some(data) => {
+			let file = format!("icon-{}.png", unix_millis(SystemTime::now()));
+			tokio::fs::write(instance_dir(&id).join(&file), data).await?;
+			Some(file)
+		}
+		None => None
+	};
+	save_instance(instance.clone()).await?;
+	Ok(instance)
+}
+
+/// Reads the icon a mod declares in its Fabric, Quilt or Forge metadata, or its `pack.png`.
+fn read_mod_icon(path: &Path) -> McResult<Vec<u8>> {
+	let mut zip = zip::ZipArchive::new(std::fs::File::open(path)?)?;
+	let mut read_entry = |name: &str| -> Option<String> {
+		let mut data = String::new();
+		zip.by_name(name).ok()?.read_to_string(&mut data).ok()?;
+		Some(data)
+	};
+
+	let mut icon = None;
+	for (file, pointer) in [("fabric.mod.json", "/icon"), ("quilt.mod.json", "/quilt_loader/metadata/icon")] {
+		let metadata = match read_entry(file).and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok()) {
+			Some(metadata) => metadata,
+			None => continue
+		};
+		icon = match metadata.pointer(pointer) {
+			Some(serde_json::Value::String(icon)) => Some(icon.clone()),
+			// sizes mapped to paths, the largest looks best
+			Some(serde_json::Value::Object(sizes)) => sizes.iter()
+				.max_by_key(|(size, _)| size.parse::<u32>().unwrap_or(0))
+				.and_then(|(_, icon)| icon.as_str())
+				.map(str::to_string),
+			_ => None
+		};
+		break;
+	}
+	if icon.is_none() {
+		icon = read_entry("META-INF/mods.toml")
+			.or_else(|| read_entry("META-INF/neoforge.mods.toml"))
+			.and_then(|data| toml::from_str::<toml::Value>(&data).ok())
+			.and_then(|metadata| metadata.get("logoFile")
+				.or_else(|| metadata.get("mods")?.get(0)?.get("logoFile"))
+				.and_then(|logo| logo.as_str())
+				.map(str::to_string));
+	}
+
+	let icon = icon.unwrap_or_else(|| "pack.png".to_string());
+	let mut data = Vec::new();
+	zip.by_name(icon.trim_start_matches('/'))
+		.map_err(|_| McError::Unsupported(format!("{} has no icon", path.display())))?
+		.read_to_end(&mut data)?;
+	Ok(data)
+}
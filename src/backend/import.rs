@@ -0,0 +1,172 @@
+mod official;
+mod prism;
+mod atlauncher;
+mod gdlauncher;
+
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::pin::Pin;
+use crate::backend::{delete_instance, game_dir, import_instance_icon, McError, McResult};
+use crate::model::Instance;
+
+pub use official::*;
+pub use prism::*;
+pub use atlauncher::*;
+pub use gdlauncher::*;
+
+/// What an import created and what it couldn't carry over.
+#[derive(Debug, Clone, Default)]
+pub struct ImportReport {
+	pub instances: Vec<Instance>,
+	/// Settings or files that weren't imported, one sentence each.
+	pub warnings: Vec<String>
+}
+
+/// Makes `to` a hard link of `from`, copying it when they are on different file systems.
+pub async fn link_or_copy(from: &Path, to: &Path) -> McResult<()> {
+	if let Some(parent) = to.parent() {
+		tokio::fs::create_dir_all(parent).await?;
+	}
+	if tokio::fs::hard_link(from, to).await.is_err() {
+		tokio::fs::copy(from, to).await?;
+	}
+	Ok(())
+}
+
+/// Links or copies every file below `from` to the same place below `to` unless it already exists
+/// there, returning the number of files added.
+pub async fn link_missing_files(from: &Path, to: &Path) -> McResult<usize> {
+	if !tokio::fs::try_exists(from).await? {
+		return Ok(0);
+	}
+	let mut added = 0;
+	let mut pending: Vec<(PathBuf, PathBuf)> = vec![(from.to_path_buf(), to.to_path_buf())];
+	while let Some((from, to)) = pending.pop() {
+		let mut entries = tokio::fs::read_dir(&from).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			let target = to.join(entry.file_name());
+			if entry.file_type().await?.is_dir() {
+				pending.push((entry.path(), target));
+			} else if !tokio::fs::try_exists(&target).await? {
+				link_or_copy(&entry.path(), &target).await?;
+				added += 1;
+			}
+		}
+	}
+	Ok(added)
+}
+
+/// Copies the directory `from` with everything in it to `to`.
+pub async fn copy_dir(from: &Path, to: &Path) -> McResult<()> {
+	let mut pending: Vec<(PathBuf, PathBuf)> = vec![(from.to_path_buf(), to.to_path_buf())];
+	while let Some((from, to)) = pending.pop() {
+		tokio::fs::create_dir_all(&to).await?;
+		let mut entries = tokio::fs::read_dir(&from).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			let target = to.join(entry.file_name());
+			if entry.file_type().await?.is_dir() {
+				pending.push((entry.path(), target));
+			} else {
+				tokio::fs::copy(entry.path(), target).await?;
+			}
+		}
+	}
+	Ok(())
+}
+
+/// Moves the directory `from` to `to`, copying it when they are on different file systems.
+pub async fn move_dir(from: &Path, to: &Path) -> McResult<()> {
+	if tokio::fs::try_exists(to).await? {
+		// only an empty directory can be replaced
+		tokio::fs::remove_dir(to).await?;
+	}
+	if tokio::fs::rename(from, to).await.is_err() {
+		copy_dir(from, to).await?;
+		// everything is in `to` already, failing here would make callers throw that copy away
+		if let Err(err) = tokio::fs::remove_dir_all(from).await {
+			eprintln!("error: failed to remove {} after copying it: {}", from.display(), err);
+		}
+	}
+	Ok(())
+}
+
+/// Finds the instances of another launcher that keeps each instance in a directory with a
+/// `marker` file. `dir` can be a single instance, the launcher directory or its `instances`
+/// directory.
+pub async fn find_instance_dirs(dir: &Path, marker: &str) -> McResult<Vec<PathBuf>> {
+	if tokio::fs::try_exists(dir.join(marker)).await? {
+		return Ok(vec![dir.to_path_buf()]);
+	}
+	let instances_dir = dir.join("instances");
+	let dir = if tokio::fs::try_exists(&instances_dir).await? { instances_dir.as_path() } else { dir };
+
+	let mut instances = Vec::new();
+	let mut entries = tokio::fs::read_dir(dir).await?;
+	while let Some(entry) = entries.next_entry().await? {
+		if tokio::fs::try_exists(entry.path().join(marker)).await? {
+			instances.push(entry.path());
+		}
+	}
+	instances.sort();
+	Ok(instances)
+}
+
+/// Imports a single instance directory of another launcher, adding what it couldn't carry over to
+/// the warnings.
+pub type ImportFuture<'a> = Pin<Box<dyn Future<Output = McResult<Instance>> + Send + 'a>>;
+
+/// Imports every instance directory with a `marker` file found by [`find_instance_dirs`] through
+/// `import`. An instance that fails to import becomes a warning rather than failing the others.
+pub async fn import_instance_dirs<F>(dir: &Path, marker: &str, import: F) -> McResult<ImportReport>
+	where F: for<'a> Fn(&'a Path, &'a mut Vec<String>) -> ImportFuture<'a> {
+	let mut report = ImportReport::default();
+	for instance_dir in find_instance_dirs(dir, marker).await? {
+		match import(&instance_dir, &mut report.warnings).await {
+			Ok(instance) => report.instances.push(instance),
+			Err(err) => report.warnings.push(format!("Failed to import {}: {}", instance_dir.display(), err))
+		}
+	}
+	if report.instances.is_empty() && report.warnings.is_empty() {
+		return Err(McError::Fs(format!("no instances found in {}", dir.display())));
+	}
+	Ok(report)
+}
+
+/// Deletes an instance created by an import when a later step of it failed, so no half imported
+/// instance is left behind. Moving the game directory has to be the last step, otherwise the
+/// moved files would be deleted with the instance.
+pub async fn discard_failed_import<T>(instance_id: &str, result: McResult<T>) -> McResult<T> {
+	if result.is_err() {
+		if let Err(err) = delete_instance(instance_id.to_string()).await {
+			eprintln!("error: failed to remove the partly imported instance: {}", err);
+		}
+	}
+	result
+}
+
+/// Makes the first of `candidates` that exists the icon of an imported instance, e.g. the icon
+/// of the modpack it was installed from. Returns the instance with its icon set.
+pub async fn import_icon(instance: Instance, candidates: Vec<PathBuf>, warnings: &mut Vec<String>) -> Instance {
+	for path in candidates {
+		if !tokio::fs::try_exists(&path).await.is_ok_and(|value| value == true) {
+			continue;
+		}
+		return match import_instance_icon(instance.id.clone(), path.clone()).await {
+			Ok(instance) => instance,
+			Err(err) => {
+				warnings.push(format!("{}: couldn't use {} as its icon: {}", instance.name, path.display(), err));
+				instance
+			}
+		};
+	}
+	instance
+}
+
+/// Copies or moves the game directory of an imported instance into the new instance.
+pub async fn import_game_dir(source: &Path, instance_id: &str, move_files: bool) -> McResult<()> {
+	if move_files {
+		move_dir(source, &game_dir(instance_id)).await
+	} else {
+		copy_dir(source, &game_dir(instance_id)).await
+	}
+}
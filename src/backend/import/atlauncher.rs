@@ -0,0 +1,152 @@
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::backend::{create_instance, discard_failed_import, import_game_dir, import_icon, import_instance_dirs, ImportReport, McError, McResult, save_instance};
+use crate::model::{InstalledMod, Instance, ModLoader, ModSource};
+
+const INSTANCE_FILE: &str = "instance.json";
+/// Image of the pack the instance was installed from.
+const INSTANCE_IMAGE: &str = "instance.png";
+
+/// ATLauncher's `instance.json`, a version JSON with the launcher settings in `launcher`.
+#[derive(Debug, Deserialize)]
+struct AtInstance {
+	/// The Minecraft version.
+	id: String,
+	launcher: AtLauncherSettings
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtLauncherSettings {
+	name: String,
+	loader_version: Option<AtLoaderVersion>,
+	#[serde(default)]
+	mods: Vec<AtMod>,
+	initial_memory: Option<u32>,
+	maximum_memory: Option<u32>,
+	java_arguments: Option<String>,
+	/// Java home directory, not the executable.
+	java_path: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+struct AtLoaderVersion {
+	version: String,
+	/// `Fabric`, `Quilt`, `Forge`, `NeoForge` or `LegacyFabric`.
+	#[serde(rename = "type")]
+	t: String
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AtMod {
+	name: String,
+	file: String,
+	/// `mods` for mods, also used for resource packs, shaders and worlds.
+	#[serde(rename = "type")]
+	t: String,
+	#[serde(default)]
+	disabled: bool,
+	curse_forge_project_id: Option<u64>,
+	curse_forge_file_id: Option<u64>,
+	modrinth_project: Option<AtModrinthId>,
+	modrinth_version: Option<AtModrinthId>
+}
+
+#[derive(Debug, Deserialize)]
+struct AtModrinthId {
+	id: String
+}
+
+/// The data directory of ATLauncher on this system when it's installed rather than portable.
+pub fn atlauncher_dir() -> Option<PathBuf> {
+	if cfg!(windows) {
+		std::env::var_os("APPDATA").map(|dir| Path::new(&dir).join("ATLauncher"))
+	} else if cfg!(target_os = "macos") {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join("Library/Application Support/ATLauncher"))
+	} else {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join(".local/share/atlauncher"))
+	}
+}
+
+/// Imports ATLauncher instances, whose directories are also their game directories.
+pub async fn import_atlauncher_instances(dir: &Path, move_files: bool) -> McResult<ImportReport> {
+	import_instance_dirs(dir, INSTANCE_FILE, |dir, warnings| Box::pin(import_atlauncher_instance(dir, move_files, warnings))).await
+}
+
+async fn import_atlauncher_instance(dir: &Path, move_files: bool, warnings: &mut Vec<String>) -> McResult<Instance> {
+	let path = dir.join(INSTANCE_FILE);
+	let at_instance: AtInstance = serde_json::from_str(&tokio::fs::read_to_string(&path).await?)
+		.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err)))?;
+	let settings = at_instance.launcher;
+	let name = settings.name;
+
+	let (loader, loader_version) = match settings.loader_version {
+		Some(loader_version) => match loader_version.t.to_lowercase().as_str() {
+			"fabric" => (ModLoader::Fabric, Some(loader_version.version)),
+			"quilt" => (ModLoader::Quilt, Some(loader_version.version)),
+			"forge" => (ModLoader::Forge, Some(loader_version.version)),
+			"neoforge" => (ModLoader::NeoForge, Some(loader_version.version)),
+			_ => {
+				warnings.push(format!("{}: couldn't translate the loader {} {}", name, loader_version.t, loader_version.version));
+				(ModLoader::Vanilla, None)
+			}
+		},
+		None => (ModLoader::Vanilla, None)
+	};
+	if matches!(loader, ModLoader::Forge | ModLoader::NeoForge) {
+		warnings.push(format!("{}: uses {}, which can't be launched yet", name, loader));
+	}
+
+	let mods: Vec<InstalledMod> = settings.mods.into_iter()
+		.filter(|at_mod| at_mod.t == "mods")
+		.map(|at_mod| {
+			let source = match (at_mod.curse_forge_project_id, at_mod.curse_forge_file_id, at_mod.modrinth_project, at_mod.modrinth_version) {
+				(_, _, Some(project), Some(version)) => Some(ModSource::Modrinth {
+					project_id: project.id,
+					version_id: version.id
+				}),
+				(Some(project_id), Some(file_id), _, _) => Some(ModSource::CurseForge { project_id, file_id }),
+				_ => None
+			};
+			InstalledMod {
+				file: at_mod.file,
+				name: at_mod.name,
+				disabled: at_mod.disabled,
+				source
+			}
+		})
+		.collect();
+
+	let mut jvm_args = Vec::new();
+	if let Some(initial) = settings.initial_memory {
+		jvm_args.push(format!("-Xms{}M", initial));
+	}
+	if let Some(maximum) = settings.maximum_memory {
+		jvm_args.push(format!("-Xmx{}M", maximum));
+	}
+	jvm_args.extend(settings.java_arguments.filter(|args| !args.trim().is_empty()));
+
+	let instance = create_instance(name, at_instance.id, loader, loader_version).await?;
+	let mut instance_settings = instance.settings.clone();
+	if !jvm_args.is_empty() {
+		instance_settings.jvm_args = Some(jvm_args.join(" "));
+	}
+	instance_settings.java_path = settings.java_path
+		.filter(|path| !path.trim().is_empty())
+		.map(|home| Path::new(&home).join("bin").join(if cfg!(windows) { "java.exe" } else { "java" }).display().to_string());
+
+	let instance = Instance {
+		settings: instance_settings,
+		mods,
+		..instance
+	};
+	let id = instance.id.clone();
+	let imported = async {
+		save_instance(instance.clone()).await?;
+		let instance = import_icon(instance, vec![dir.join(INSTANCE_IMAGE)], warnings).await;
+		import_game_dir(dir, &instance.id, move_files).await?;
+		Ok::<_, McError>(instance)
+	}.await;
+	discard_failed_import(&id, imported).await
+}
@@ -0,0 +1,137 @@
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::backend::{create_instance, discard_failed_import, import_game_dir, import_icon, import_instance_dirs, ImportReport, McError, McResult, save_instance};
+use crate::model::{InstalledMod, Instance, ModLoader, ModSource};
+
+const CONFIG_FILE: &str = "config.json";
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GdConfig {
+	loader: GdLoader,
+	#[serde(default)]
+	mods: Vec<GdMod>,
+	java_args: Option<String>,
+	/// Image of the instance or its modpack, a file in the instance directory.
+	background: Option<String>,
+	/// Maximum memory in MB.
+	java_memory: Option<u32>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GdLoader {
+	/// `vanilla`, `fabric`, `forge` or `quilt`.
+	loader_type: String,
+	mc_version: String,
+	loader_version: Option<String>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GdMod {
+	file_name: String,
+	display_name: Option<String>,
+	/// `curseforge` or `modrinth`, older configs only have CurseForge mods and leave it out.
+	source: Option<String>,
+	/// A number for CurseForge and a string for Modrinth.
+	#[serde(rename = "projectID")]
+	project_id: Option<serde_json::Value>,
+	#[serde(rename = "fileID")]
+	file_id: Option<serde_json::Value>
+}
+
+/// The data directory of GDLauncher on this system.
+pub fn gdlauncher_dir() -> Option<PathBuf> {
+	if cfg!(windows) {
+		std::env::var_os("APPDATA").map(|dir| Path::new(&dir).join("gdlauncher_next"))
+	} else if cfg!(target_os = "macos") {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join("Library/Application Support/gdlauncher_next"))
+	} else {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join(".config/gdlauncher_next"))
+	}
+}
+
+/// Imports GDLauncher instances, whose directories are also their game directories.
+pub async fn import_gdlauncher_instances(dir: &Path, move_files: bool) -> McResult<ImportReport> {
+	import_instance_dirs(dir, CONFIG_FILE, |dir, warnings| Box::pin(import_gdlauncher_instance(dir, move_files, warnings))).await
+}
+
+async fn import_gdlauncher_instance(dir: &Path, move_files: bool, warnings: &mut Vec<String>) -> McResult<Instance> {
+	let path = dir.join(CONFIG_FILE);
+	let config: GdConfig = serde_json::from_str(&tokio::fs::read_to_string(&path).await?)
+		.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err)))?;
+	// GDLauncher names instances after their directory
+	let name = dir.file_name().unwrap_or_default().to_string_lossy().into_owned();
+
+	let loader = match config.loader.loader_type.as_str() {
+		"vanilla" => ModLoader::Vanilla,
+		"fabric" => ModLoader::Fabric,
+		"quilt" => ModLoader::Quilt,
+		"forge" => ModLoader::Forge,
+		"neoforge" => ModLoader::NeoForge,
+		loader_type => {
+			warnings.push(format!("{}: couldn't translate the loader {}", name, loader_type));
+			ModLoader::Vanilla
+		}
+	};
+	let background = config.background.filter(|background| !background.is_empty());
+	let loader_version = config.loader.loader_version.filter(|_| loader != ModLoader::Vanilla);
+	if matches!(loader, ModLoader::Forge | ModLoader::NeoForge) {
+		warnings.push(format!("{}: uses {}, which can't be launched yet", name, loader));
+	}
+
+	let mods: Vec<InstalledMod> = config.mods.into_iter()
+		.map(|gd_mod| {
+			let source = match (gd_mod.source.as_deref(), gd_mod.project_id, gd_mod.file_id) {
+				(Some("modrinth"), Some(project_id), Some(file_id)) => Some(ModSource::Modrinth {
+					project_id: json_id(project_id),
+					version_id: json_id(file_id)
+				}),
+				(None | Some("curseforge"), Some(project_id), Some(file_id)) => project_id.as_u64()
+					.zip(file_id.as_u64())
+					.map(|(project_id, file_id)| ModSource::CurseForge { project_id, file_id }),
+				_ => None
+			};
+			InstalledMod {
+				disabled: gd_mod.file_name.ends_with(".disabled"),
+				name: gd_mod.display_name.unwrap_or_else(|| gd_mod.file_name.clone()),
+				file: gd_mod.file_name,
+				source
+			}
+		})
+		.collect();
+
+	let mut jvm_args = Vec::new();
+	if let Some(memory) = config.java_memory {
+		jvm_args.push(format!("-Xmx{}M", memory));
+	}
+	jvm_args.extend(config.java_args.filter(|args| !args.trim().is_empty()));
+
+	let instance = create_instance(name, config.loader.mc_version, loader, loader_version).await?;
+	let mut settings = instance.settings.clone();
+	if !jvm_args.is_empty() {
+		settings.jvm_args = Some(jvm_args.join(" "));
+	}
+	let instance = Instance {
+		settings,
+		mods,
+		..instance
+	};
+	let id = instance.id.clone();
+	let imported = async {
+		save_instance(instance.clone()).await?;
+		let instance = import_icon(instance, background.into_iter().map(|background| dir.join(background)).collect(), warnings).await;
+		import_game_dir(dir, &instance.id, move_files).await?;
+		Ok::<_, McError>(instance)
+	}.await;
+	discard_failed_import(&id, imported).await
+}
+
+/// A Modrinth id, which GDLauncher stores as a string but some versions wrote as a number.
+fn json_id(value: serde_json::Value) -> String {
+	match value {
+		serde_json::Value::String(id) => id,
+		value => value.to_string()
+	}
+}
@@ -0,0 +1,154 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::backend::{cache_dir, create_instance, ImportReport, link_missing_files, link_or_copy, McError, McResult, save_instance};
+use crate::model::{all_versions, Instance, ModLoader};
+
+const LAUNCHER_PROFILES_FILE: &str = "launcher_profiles.json";
+
+#[derive(Debug, Deserialize)]
+struct LauncherProfiles {
+	profiles: HashMap<String, LauncherProfile>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LauncherProfile {
+	#[serde(default)]
+	name: String,
+	/// `custom`, `latest-release` or `latest-snapshot`.
+	#[serde(rename = "type", default)]
+	t: String,
+	last_version_id: Option<String>,
+	game_dir: Option<PathBuf>,
+	java_args: Option<String>,
+	/// Path of the Java executable, despite the name.
+	java_dir: Option<String>,
+	resolution: Option<Resolution>
+}
+
+#[derive(Debug, Deserialize)]
+struct Resolution {
+	width: u32,
+	height: u32
+}
+
+/// The default `.minecraft` directory of the official launcher on this system.
+pub fn official_launcher_dir() -> Option<PathBuf> {
+	if cfg!(windows) {
+		std::env::var_os("APPDATA").map(|dir| Path::new(&dir).join(".minecraft"))
+	} else if cfg!(target_os = "macos") {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join("Library/Application Support/minecraft"))
+	} else {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join(".minecraft"))
+	}
+}
+
+/// Creates an instance for every profile in `dir/launcher_profiles.json`. The instances keep
+/// using the game directories of the profiles. With `reuse_files` the libraries, assets and
+/// vanilla versions the official launcher already downloaded are linked into the cache directory.
+pub async fn import_official_profiles(dir: &Path, latest: &all_versions::Latest, reuse_files: bool) -> McResult<ImportReport> {
+	let path = dir.join(LAUNCHER_PROFILES_FILE);
+	let data = tokio::fs::read_to_string(&path).await?;
+	let profiles: LauncherProfiles = serde_json::from_str(&data)
+		.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err)))?;
+
+	let mut report = ImportReport::default();
+	let mut profiles: Vec<LauncherProfile> = profiles.profiles.into_values().collect();
+	profiles.sort_by(|a, b| a.name.cmp(&b.name));
+	for profile in profiles {
+		let version_id = match profile.t.as_str() {
+			"latest-release" => latest.release.clone(),
+			"latest-snapshot" => latest.snapshot.clone(),
+			_ => match &profile.last_version_id {
+				Some(id) => id.clone(),
+				None => {
+					report.warnings.push(format!("{} has no version, skipped it", profile.name));
+					continue;
+				}
+			}
+		};
+		let name = match (profile.name.as_str(), profile.t.as_str()) {
+			("", "latest-release") => "Latest release".to_string(),
+			("", "latest-snapshot") => "Latest snapshot".to_string(),
+			("", _) => version_id.clone(),
+			(name, _) => name.to_string()
+		};
+
+		let (version, loader, loader_version) = resolve_version(dir, &version_id).await;
+		if matches!(loader, ModLoader::Forge | ModLoader::NeoForge) {
+			report.warnings.push(format!("{} uses {}, which can't be launched yet", name, loader));
+		}
+		if reuse_files && loader == ModLoader::Vanilla {
+			reuse_version(dir, &version).await?;
+		}
+
+		let instance = create_instance(name, version, loader, loader_version).await?;
+		let mut settings = instance.settings.clone();
+		settings.game_dir = Some(match profile.game_dir {
+			Some(game_dir) => game_dir,
+			None => dir.to_path_buf()
+		});
+		settings.jvm_args = profile.java_args.filter(|args| !args.trim().is_empty());
+		settings.java_path = profile.java_dir.filter(|path| !path.trim().is_empty());
+		if let Some(resolution) = profile.resolution {
+			settings.width = Some(resolution.width);
+			settings.height = Some(resolution.height);
+		}
+		let instance = Instance { settings, ..instance };
+		save_instance(instance.clone()).await?;
+		report.instances.push(instance);
+	}
+
+	if reuse_files {
+		link_missing_files(&dir.join("libraries"), &cache_dir().join("libraries")).await?;
+		link_missing_files(&dir.join("assets/indexes"), &cache_dir().join("assets/indexes")).await?;
+		link_missing_files(&dir.join("assets/objects"), &cache_dir().join("assets/objects")).await?;
+	}
+	Ok(report)
+}
+
+/// Finds the Minecraft version and mod loader behind a version id of the official launcher.
+/// Loader installers add versions like `fabric-loader-0.15.3-1.20.4` that inherit from the
+/// vanilla one.
+async fn resolve_version(dir: &Path, id: &str) -> (String, ModLoader, Option<String>) {
+	let path = dir.join("versions").join(id).join(format!("{}.json", id));
+	let inherits_from = tokio::fs::read_to_string(&path).await.ok()
+		.and_then(|data| serde_json::from_str::<serde_json::Value>(&data).ok())
+		.and_then(|version| version["inheritsFrom"].as_str().map(str::to_string));
+	let version = match inherits_from {
+		Some(version) => version,
+		None => return (id.to_string(), ModLoader::Vanilla, None)
+	};
+
+	let loader_version = |prefix: &str| id.strip_prefix(prefix)
+		.and_then(|rest| rest.strip_suffix(&format!("-{}", version)))
+		.map(str::to_string);
+	let (loader, loader_version) = if id.starts_with("fabric-loader-") {
+		(ModLoader::Fabric, loader_version("fabric-loader-"))
+	} else if id.starts_with("quilt-loader-") {
+		(ModLoader::Quilt, loader_version("quilt-loader-"))
+	} else if let Some(loader_version) = id.strip_prefix("neoforge-") {
+		(ModLoader::NeoForge, Some(loader_version.to_string()))
+	} else if let Some((_, loader_version)) = id.split_once("-forge-") {
+		(ModLoader::Forge, Some(loader_version.to_string()))
+	} else {
+		(ModLoader::Vanilla, None)
+	};
+	(version, loader, loader_version)
+}
+
+/// Links the version JSON and client jar of a vanilla version into the cache directory.
+async fn reuse_version(dir: &Path, version: &str) -> McResult<()> {
+	let version_dir = dir.join("versions").join(version);
+	for (file, target) in [
+		(format!("{}.json", version), cache_dir().join(format!("versions/{}.json", version))),
+		(format!("{}.jar", version), cache_dir().join(format!("clients/{}.jar", version)))
+	] {
+		let file = version_dir.join(file);
+		if tokio::fs::try_exists(&file).await? && !tokio::fs::try_exists(&target).await? {
+			link_or_copy(&file, &target).await?;
+		}
+	}
+	Ok(())
+}
@@ -0,0 +1,168 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use serde::Deserialize;
+use crate::backend::{create_instance, discard_failed_import, import_game_dir, import_icon, import_instance_dirs, ImportReport, McError, McResult, save_instance};
+use crate::model::{Instance, ModLoader};
+
+const INSTANCE_CFG_FILE: &str = "instance.cfg";
+const PACK_FILE: &str = "mmc-pack.json";
+/// Components that come with the Minecraft version or the mod loader and need no translation.
+const IMPLIED_COMPONENTS: &[&str] = &["org.lwjgl", "org.lwjgl3", "net.fabricmc.intermediary", "org.quiltmc.hashed"];
+
+#[derive(Debug, Deserialize)]
+struct Pack {
+	components: Vec<Component>
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Component {
+	uid: String,
+	version: Option<String>,
+	cached_name: Option<String>
+}
+
+/// The data directory of Prism Launcher on this system, MultiMC is portable and has none.
+pub fn prism_launcher_dir() -> Option<PathBuf> {
+	if cfg!(windows) {
+		std::env::var_os("APPDATA").map(|dir| Path::new(&dir).join("PrismLauncher"))
+	} else if cfg!(target_os = "macos") {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join("Library/Application Support/PrismLauncher"))
+	} else {
+		std::env::var_os("HOME").map(|dir| Path::new(&dir).join(".local/share/PrismLauncher"))
+	}
+}
+
+/// Imports MultiMC or Prism Launcher instances. `dir` can be a single instance, the launcher
+/// directory or its `instances` directory. The game directories are copied, or moved with
+/// `move_files`.
+pub async fn import_prism_instances(dir: &Path, move_files: bool) -> McResult<ImportReport> {
+	import_instance_dirs(dir, INSTANCE_CFG_FILE, |dir, warnings| Box::pin(import_prism_instance(dir, move_files, warnings))).await
+}
+
+async fn import_prism_instance(dir: &Path, move_files: bool, warnings: &mut Vec<String>) -> McResult<Instance> {
+	let cfg_path = dir.join(INSTANCE_CFG_FILE);
+	let cfg = parse_instance_cfg(&tokio::fs::read_to_string(&cfg_path).await?);
+	let pack_path = dir.join(PACK_FILE);
+	let pack: Pack = serde_json::from_str(&tokio::fs::read_to_string(&pack_path).await?)
+		.map_err(|err| McError::Fs(format!("failed to parse {}: {}", pack_path.display(), err)))?;
+
+	let name = match cfg.get("name") {
+		Some(name) if !name.is_empty() => name.clone(),
+		_ => dir.file_name().unwrap_or_default().to_string_lossy().into_owned()
+	};
+
+	let mut version = None;
+	let mut loader = ModLoader::Vanilla;
+	let mut loader_version = None;
+	for component in pack.components {
+		let component_loader = match component.uid.as_str() {
+			"net.minecraft" => {
+				version = component.version;
+				continue;
+			}
+			"net.fabricmc.fabric-loader" => ModLoader::Fabric,
+			"org.quiltmc.quilt-loader" => ModLoader::Quilt,
+			"net.minecraftforge" => ModLoader::Forge,
+			"net.neoforged" => ModLoader::NeoForge,
+			uid if IMPLIED_COMPONENTS.contains(&uid) => continue,
+			_ => {
+				warnings.push(format!(
+					"{}: couldn't translate the component {} {}",
+					name,
+					component.cached_name.as_deref().unwrap_or(&component.uid),
+					component.version.as_deref().unwrap_or("")
+				));
+				continue;
+			}
+		};
+		if loader != ModLoader::Vanilla {
+			warnings.push(format!("{}: has both {} and {}, kept {}", name, loader, component_loader, loader));
+			continue;
+		}
+		loader = component_loader;
+		loader_version = component.version;
+	}
+	let version = version.ok_or_else(|| McError::Unsupported(format!("{} has no Minecraft version", name)))?;
+	if matches!(loader, ModLoader::Forge | ModLoader::NeoForge) {
+		warnings.push(format!("{}: uses {}, which can't be launched yet", name, loader));
+	}
+
+	let instance = create_instance(name.clone(), version, loader, loader_version).await?;
+	let mut settings = instance.settings.clone();
+	let enabled = |key: &str| cfg.get(key).is_some_and(|value| value == "true");
+	let value = |key: &str| cfg.get(key).filter(|value| !value.trim().is_empty()).cloned();
+
+	let mut jvm_args = Vec::new();
+	if enabled("OverrideMemory") {
+		if let Some(min) = value("MinMemAlloc") {
+			jvm_args.push(format!("-Xms{}M", min));
+		}
+		if let Some(max) = value("MaxMemAlloc") {
+			jvm_args.push(format!("-Xmx{}M", max));
+		}
+	}
+	if enabled("OverrideJavaArgs") {
+		jvm_args.extend(value("JvmArgs"));
+	}
+	if !jvm_args.is_empty() {
+		settings.jvm_args = Some(jvm_args.join(" "));
+	}
+	if enabled("OverrideJavaLocation") {
+		settings.java_path = value("JavaPath");
+	}
+	if enabled("OverrideCommands") {
+		settings.wrapper_command = value("WrapperCommand");
+		settings.pre_launch_command = value("PreLaunchCommand");
+		settings.post_exit_command = value("PostExitCommand");
+		if [settings.pre_launch_command.as_deref(), settings.post_exit_command.as_deref()].into_iter()
+			.flatten()
+			.any(|command| command.contains("$INST_")) {
+			warnings.push(format!("{}: its commands use $INST_ variables, which aren't set by this launcher", name));
+		}
+	}
+	if enabled("OverrideWindow") {
+		settings.width = value("MinecraftWinWidth").and_then(|width| width.parse().ok());
+		settings.height = value("MinecraftWinHeight").and_then(|height| height.parse().ok());
+		settings.fullscreen = enabled("LaunchMaximized");
+	}
+
+	// custom icons are in the icons folder of the launcher, or in the instance itself
+	let mut icons = Vec::new();
+	if let Some(key) = value("iconKey").filter(|key| key != "default") {
+		let file = format!("{}.png", key);
+		icons.push(dir.join(&file));
+		if let Some(launcher_dir) = dir.parent().and_then(Path::parent) {
+			icons.push(launcher_dir.join("icons").join(&file));
+		}
+	}
+
+	let instance = Instance { settings, ..instance };
+	let id = instance.id.clone();
+	let imported = async {
+		save_instance(instance.clone()).await?;
+		let instance = import_icon(instance, icons, warnings).await;
+		for game_dir_name in [".minecraft", "minecraft"] {
+			let source = dir.join(game_dir_name);
+			if tokio::fs::try_exists(&source).await? {
+				import_game_dir(&source, &instance.id, move_files).await?;
+				break;
+			}
+		}
+		Ok::<_, McError>(instance)
+	}.await;
+	discard_failed_import(&id, imported).await
+}
+
+/// Reads the `key=value` lines of an `instance.cfg`, ignoring sections.
+fn parse_instance_cfg(data: &str) -> HashMap<String, String> {
+	data.lines()
+		.filter(|line| !line.starts_with('[') && !line.starts_with('#'))
+		.filter_map(|line| line.split_once('='))
+		.map(|(key, value)| {
+			let value = value.trim();
+			let value = value.strip_prefix('"').and_then(|value| value.strip_suffix('"')).unwrap_or(value);
+			(key.trim().to_string(), value.to_string())
+		})
+		.collect()
+}
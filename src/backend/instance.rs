@@ -0,0 +1,363 @@
+use std::path::{Path, PathBuf};
+use crate::backend::{ARCHIVE_FOLDERS, clone_dir, data_dir, EXCLUDED_NAMES, LaunchCommand, load_history, McError, McResult, QUICK_PLAY_LOG, version_installed};
+use crate::model::{EnvAction, EnvVar, Instance, InstanceSettings, ModLoader, QuickPlayEntry};
+
+/// Directory of the instances, relative to the data directory.
+pub const INSTANCES_DIR: &str = "instances";
+const INSTANCE_FILE: &str = "instance.toml";
+/// Directory inside an instance directory the game runs in.
+const GAME_DIR: &str = "minecraft";
+/// The single game directory used before instances existed, relative to the data directory.
+const LEGACY_INSTANCE_DIR: &str = "instance";
+const LEGACY_SETTINGS_FILE: &str = "settings.toml";
+const LEGACY_HISTORY_FILE: &str = "history.json";
+/// Files of an instance directory that belong to the instance itself rather than its contents.
+const NOT_DUPLICATED: &[&str] = &["history.json", "exports", GAME_LOGS_DIR];
+/// Launch logs of an instance, relative to its directory.
+const GAME_LOGS_DIR: &str = "logs";
+/// Files copied from a game directory outside of the instance besides [`ARCHIVE_FOLDERS`].
+const DUPLICATED_GAME_FILES: &[&str] = &["options.txt"];
+
+/// What the home screen shows about an instance besides its configuration.
+#[derive(Debug, Clone, Default)]
+pub struct InstanceInfo {
+	pub installed: bool,
+	/// Start of the last launch in milliseconds since the unix epoch.
+	pub last_played: Option<u64>
+}
+
+pub fn instances_dir() -> PathBuf {
+	data_dir().join(INSTANCES_DIR)
+}
+
+pub fn instance_dir(id: &str) -> PathBuf {
+	instances_dir().join(id)
+}
+
+/// Where the output of each launch of an instance is logged.
+pub fn game_logs_dir(id: &str) -> PathBuf {
+	instance_dir(id).join(GAME_LOGS_DIR)
+}
+
+/// The game directory of an instance, what `${game_directory}` points at.
+pub fn game_dir(id: &str) -> PathBuf {
+	instance_dir(id).join(GAME_DIR)
+}
+
+/// The game directory an instance actually runs in, which may be outside of its directory.
+pub fn instance_game_dir(instance: &Instance) -> PathBuf {
+	instance.settings.game_dir.clone().unwrap_or_else(|| game_dir(&instance.id))
+}
+
+/// Lists all instances sorted by name.
+pub async fn list_instances() -> McResult<Vec<Instance>> {
+	let mut entries = match tokio::fs::read_dir(instances_dir()).await {
+		Ok(entries) => entries,
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => return Ok(Vec::new()),
+		Err(err) => return Err(McError::from(err))
+	};
+
+	let mut instances = Vec::new();
+	while let Some(entry) = entries.next_entry().await? {
+		let id = match entry.file_name().to_str() {
+			Some(id) => id.to_string(),
+			None => continue
+		};
+		if !tokio::fs::try_exists(entry.path().join(INSTANCE_FILE)).await.is_ok_and(|value| value == true) {
+			continue;
+		}
+		match load_instance(&id).await {
+			Ok(instance) => instances.push(instance),
+			Err(err) => eprintln!("error: skipping instance {}: {}", id, err)
+		}
+	}
+	instances.sort_by(|a, b| a.name.to_lowercase().cmp(&b.name.to_lowercase()));
+	Ok(instances)
+}
+
+pub async fn load_instance(id: &str) -> McResult<Instance> {
+	let path = instance_dir(id).join(INSTANCE_FILE);
+	let data = tokio::fs::read_to_string(&path).await?;
+	let mut instance: Instance = toml::from_str(&data)
+		.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err)))?;
+	instance.id = id.to_string();
+	Ok(instance)
+}
+
+pub async fn save_instance(instance: Instance) -> McResult<()> {
+	let dir = instance_dir(&instance.id);
+	tokio::fs::create_dir_all(&dir).await?;
+	tokio::fs::write(dir.join(INSTANCE_FILE), toml::to_string(&instance).unwrap()).await?;
+	Ok(())
+}
+
+/// Creates a new instance with an id derived from `name` and an empty game directory.
+pub async fn create_instance(name: String, version: String, loader: ModLoader, loader_version: Option<String>) -> McResult<Instance> {
+	tokio::fs::create_dir_all(instances_dir()).await?;
+	let base = instance_id(&name);
+	let mut id = base.clone();
+	let mut suffix = 2;
+	while tokio::fs::try_exists(instance_dir(&id)).await? {
+		id = format!("{}-{}", base, suffix);
+		suffix += 1;
+	}
+
+	let instance = Instance {
+		id,
+		name,
+		version,
+		loader,
+		loader_version,
+		group: None,
+		icon: None,
+		notes: String::new(),
+		favourite: false,
+		settings: InstanceSettings::default(),
+		mods: Vec::new()
+	};
+	tokio::fs::create_dir_all(game_dir(&instance.id)).await?;
+	save_instance(instance.clone()).await?;
+	Ok(instance)
+}
+
+pub async fn load_instance_info(instance: &Instance) -> McResult<InstanceInfo> {
+	let history = load_history(&instance_dir(&instance.id)).await?;
+	Ok(InstanceInfo {
+		installed: version_installed(&instance.version).await,
+		last_played: history.last().map(|record| record.started_at)
+	})
+}
+
+/// Copies an instance including its game directory, but without its launch history. Files of the
+/// game directory share their data with the original where the file system allows it.
+pub async fn duplicate_instance(id: String, include_saves: bool) -> McResult<Instance> {
+	let source = load_instance(&id).await?;
+	let copy = create_instance(
+		format!("{} (copy)", source.name),
+		source.version.clone(),
+		source.loader,
+		source.loader_version.clone()
+	).await?;
+	if let Err(err) = clone_instance_files(&source, &copy, include_saves).await {
+		if let Err(delete_err) = delete_instance(copy.id).await {
+			eprintln!("error: failed to remove the partial copy: {}", delete_err);
+		}
+		return Err(err);
+	}
+
+	let mut settings = source.settings;
+	settings.game_dir = None;
+	let copy = Instance {
+		group: source.group,
+		icon: source.icon,
+		notes: source.notes,
+		settings,
+		mods: source.mods,
+		..copy
+	};
+	save_instance(copy.clone()).await?;
+	Ok(copy)
+}
+
+async fn clone_instance_files(source: &Instance, copy: &Instance, include_saves: bool) -> McResult<()> {
+	// everything besides the game directory, e.g. files other tools put there
+	let mut skip: Vec<String> = NOT_DUPLICATED.iter().map(|name| name.to_string()).collect();
+	skip.extend([INSTANCE_FILE.to_string(), GAME_DIR.to_string()]);
+	clone_dir(instance_dir(&source.id), instance_dir(&copy.id), skip).await?;
+
+	// a game directory outside of the instance becomes the copy's own, but it may be a whole
+	// .minecraft with versions, libraries and credentials of another launcher
+	let source_game_dir = instance_game_dir(source);
+	let mut skip: Vec<String> = EXCLUDED_NAMES.iter().map(|name| name.to_string()).collect();
+	if source.settings.game_dir.is_some() {
+		let mut entries = tokio::fs::read_dir(&source_game_dir).await?;
+		while let Some(entry) = entries.next_entry().await? {
+			let name = entry.file_name().to_string_lossy().into_owned();
+			if !ARCHIVE_FOLDERS.contains(&name.as_str()) && !DUPLICATED_GAME_FILES.contains(&name.as_str()) {
+				skip.push(name);
+			}
+		}
+	}
+	if !include_saves {
+		skip.push("saves".to_string());
+	}
+	clone_dir(source_game_dir, game_dir(&copy.id), skip).await
+}
+
+/// Opens `path` in the system file manager.
+pub async fn open_folder(path: PathBuf) -> McResult<()> {
+	tokio::fs::create_dir_all(&path).await?;
+	let program = if cfg!(windows) {
+		"explorer"
+	} else if cfg!(target_os = "macos") {
+		"open"
+	} else {
+		"xdg-open"
+	};
+	tokio::process::Command::new(program)
+		.arg(&path)
+		.spawn()
+		.map_err(|err| McError::Process(format!("failed to run {}: {}", program, err)))?;
+	Ok(())
+}
+
+/// Deletes an instance together with the game directory inside it. A game directory set in the
+/// settings belongs to the user or another launcher and is kept.
+pub async fn delete_instance(id: String) -> McResult<()> {
+	let dir = instance_dir(&id);
+	if !tokio::fs::try_exists(dir.join(INSTANCE_FILE)).await? {
+		return Err(McError::Fs(format!("{} is not an instance", dir.display())));
+	}
+	tokio::fs::remove_dir_all(dir).await?;
+	Ok(())
+}
+
+/// Turns `name` into a directory name, e.g. `My Pack 1.20` into `my-pack-1-20`.
+fn instance_id(name: &str) -> String {
+	let mut id = String::new();
+	for c in name.chars() {
+		if c.is_ascii_alphanumeric() {
+			id.push(c.to_ascii_lowercase());
+		} else if !id.is_empty() && !id.ends_with('-') {
+			id.push('-');
+		}
+	}
+	let id = id.trim_end_matches('-');
+	if id.is_empty() {
+		"instance".to_string()
+	} else {
+		id.to_string()
+	}
+}
+
+/// Turns the old `instance` directory into the `default` instance. Run once on startup.
+pub async fn migrate_legacy_instance() -> McResult<()> {
+	let legacy_dir = data_dir().join(LEGACY_INSTANCE_DIR);
+	if !tokio::fs::try_exists(&legacy_dir).await? {
+		return Ok(());
+	}
+	let id = "default";
+	if tokio::fs::try_exists(instance_dir(id)).await? {
+		return Err(McError::Fs(format!(
+			"both {} and {} exist, move the old directory out of the way",
+			legacy_dir.display(),
+			instance_dir(id).display()
+		)));
+	}
+
+	tokio::fs::create_dir_all(instance_dir(id)).await?;
+	tokio::fs::rename(&legacy_dir, game_dir(id)).await?;
+
+	let settings_file = game_dir(id).join(LEGACY_SETTINGS_FILE);
+	let settings = match tokio::fs::read_to_string(&settings_file).await {
+		Ok(data) => {
+			let settings = toml::from_str(&data)
+				.map_err(|err| McError::Fs(format!("failed to parse {}: {}", settings_file.display(), err)))?;
+			tokio::fs::remove_file(&settings_file).await?;
+			settings
+		}
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => InstanceSettings::default(),
+		Err(err) => return Err(McError::from(err))
+	};
+
+	let history_file = game_dir(id).join(LEGACY_HISTORY_FILE);
+	if tokio::fs::try_exists(&history_file).await? {
+		tokio::fs::rename(&history_file, instance_dir(id).join(LEGACY_HISTORY_FILE)).await?;
+	}
+	// the old directory didn't record its version, the last launched one is the best guess
+	let version = load_history(&instance_dir(id)).await?
+		.last()
+		.map(|record| record.version.clone())
+		.unwrap_or_default();
+
+	save_instance(Instance {
+		id: id.to_string(),
+		name: "Default".to_string(),
+		version,
+		loader: ModLoader::Vanilla,
+		loader_version: None,
+		group: None,
+		icon: None,
+		notes: String::new(),
+		favourite: false,
+		settings,
+		mods: Vec::new()
+	}).await
+}
+
+/// Reads the worlds, servers and realms the game recorded in its quick play log, newest first.
+pub async fn read_quick_play_log(game_dir: &Path) -> McResult<Vec<QuickPlayEntry>> {
+	let path = game_dir.join(QUICK_PLAY_LOG);
+	match tokio::fs::read_to_string(&path).await {
+		Ok(data) => {
+			let mut entries: Vec<QuickPlayEntry> = serde_json::from_str(&data)
+				.map_err(|err| McError::Fs(format!("failed to parse {}: {}", path.display(), err)))?;
+			entries.reverse();
+			Ok(entries)
+		}
+		Err(err) if err.kind() == tokio::io::ErrorKind::NotFound => Ok(Vec::new()),
+		Err(err) => Err(McError::from(err))
+	}
+}
+
+/// Turns the environment settings of an instance into the variables to set (`Some`) or remove
+/// (`None`) on the game process. Entries are applied in order, so a prepended value is joined with
+/// what earlier entries left, or the launcher's own value if there were none.
+pub fn resolve_env(env: &[EnvVar]) -> Vec<(String, Option<String>)> {
+	let separator = if cfg!(windows) { ';' } else { ':' };
+	let mut resolved: Vec<(String, Option<String>)> = Vec::new();
+	for var in env.iter().filter(|var| !var.name.is_empty()) {
+		let index = match resolved.iter().position(|(name, _)| *name == var.name) {
+			Some(index) => index,
+			None => {
+				resolved.push((var.name.clone(), std::env::var(&var.name).ok()));
+				resolved.len() - 1
+			}
+		};
+		let value = &mut resolved[index].1;
+		*value = match var.action {
+			EnvAction::Set => Some(var.value.clone()),
+			EnvAction::Unset => None,
+			EnvAction::Prepend => match value.take() {
+				Some(current) if !current.is_empty() => Some(format!("{}{}{}", var.value, separator, current)),
+				_ => Some(var.value.clone())
+			}
+		};
+	}
+	resolved
+}
+
+/// Runs a pre-launch or post-exit hook through the system shell inside the game directory. The
+/// hook gets the instance path, version and exit code (post-exit only, 128 plus the signal number
+/// for games killed by a signal) as environment variables.
+pub async fn run_hook(command: &str, launch: &LaunchCommand, exit_code: Option<i32>) -> McResult<()> {
+	let mut shell = if cfg!(windows) {
+		let mut shell = tokio::process::Command::new("cmd");
+		shell.arg("/C");
+		shell
+	} else {
+		let mut shell = tokio::process::Command::new("sh");
+		shell.arg("-c");
+		shell
+	};
+	shell.arg(command)
+		.current_dir(&launch.working_dir)
+		.env("HZLAUNCHER_INSTANCE_DIR", &launch.working_dir)
+		.env("HZLAUNCHER_VERSION", &launch.version);
+	if let Some(code) = exit_code {
+		shell.env("HZLAUNCHER_EXIT_CODE", code.to_string());
+	}
+
+	let output = shell.output().await
+		.map_err(|err| McError::Process(format!("failed to run `{}`: {}", command, err)))?;
+	if output.status.success() {
+		Ok(())
+	} else {
+		Err(McError::Process(format!(
+			"`{}` failed with {}: {}",
+			command,
+			output.status,
+			String::from_utf8_lossy(&output.stderr).trim()
+		)))
+	}
+}
@@ -0,0 +1,98 @@
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use crate::backend::{LaunchCommand, McError, McResult};
+
+const EXPORTS_DIR: &str = "exports";
+/// Variable the exported script reads the access token from when it isn't embedded.
+pub const ACCESS_TOKEN_VAR: &str = "MC_ACCESS_TOKEN";
+
+/// Builds a POSIX shell script that runs `launch` exactly like the launcher does. With
+/// `token_placeholder` every occurrence of `access_token` is read from [`ACCESS_TOKEN_VAR`] instead.
+pub fn launch_script(launch: &LaunchCommand, access_token: &str, token_placeholder: bool) -> String {
+	let quote = |value: &str| if token_placeholder && !access_token.is_empty() {
+		value.split(access_token)
+			.map(shell_quote)
+			.collect::<Vec<_>>()
+			.join(&format!("\"${{{}}}\"", ACCESS_TOKEN_VAR))
+	} else {
+		shell_quote(value)
+	};
+
+	let mut script = format!("#!/bin/sh\n# Launches Minecraft {} without the launcher\nset -e\n", launch.version);
+	if token_placeholder {
+		script.push_str(&format!(
+			": \"${{{}:?set it to the Minecraft access token}}\"\n",
+			ACCESS_TOKEN_VAR
+		));
+	}
+	script.push_str(&format!("cd {}\n", shell_quote(launch.working_dir.to_str().unwrap())));
+	for (name, value) in &launch.env {
+		match value {
+			Some(value) => script.push_str(&format!("export {}={}\n", name, quote(value))),
+			None => script.push_str(&format!("unset {}\n", name))
+		}
+	}
+
+	script.push_str("exec");
+	for part in launch.wrapper.iter().chain([&launch.program]).chain(&launch.args) {
+		script.push_str(" \\\n\t");
+		script.push_str(&quote(part));
+	}
+	script.push('\n');
+	script
+}
+
+/// Writes `script` to `<dir>/exports/launch-<version>.sh` and returns its path. The script is
+/// only readable by the owner since it may contain the access token.
+pub async fn write_launch_script(dir: &Path, version: &str, script: &str) -> McResult<PathBuf> {
+	let dir = dir.join(EXPORTS_DIR);
+	tokio::fs::create_dir_all(&dir).await?;
+	let path = dir.join(format!("launch-{}.sh", version));
+	// recreated rather than truncated, an older script may have been readable by others
+	match tokio::fs::remove_file(&path).await {
+		Err(err) if err.kind() != tokio::io::ErrorKind::NotFound => return Err(McError::from(err)),
+		_ => {}
+	}
+	let mut options = tokio::fs::OpenOptions::new();
+	options.write(true).create_new(true);
+	#[cfg(unix)]
+	options.mode(0o700);
+	let mut file = options.open(&path).await?;
+	file.write_all(script.as_bytes()).await?;
+	file.flush().await?;
+	Ok(path)
+}
+
+/// Formats `launch` as a single shell command line, prefixed with `cd` and the environment changes.
+pub fn command_line(launch: &LaunchCommand) -> String {
+	let mut line = format!("cd {} &&", shell_quote(launch.working_dir.to_str().unwrap()));
+	let unset: Vec<&str> = launch.env.iter()
+		.filter(|(_, value)| value.is_none())
+		.map(|(name, _)| name.as_str())
+		.collect();
+	if !unset.is_empty() {
+		line.push_str(" env");
+		for name in unset {
+			line.push_str(" -u ");
+			line.push_str(name);
+		}
+	}
+	for (name, value) in &launch.env {
+		if let Some(value) = value {
+			line.push_str(&format!(" {}={}", name, shell_quote(value)));
+		}
+	}
+	for part in launch.wrapper.iter().chain([&launch.program]).chain(&launch.args) {
+		line.push(' ');
+		line.push_str(&shell_quote(part));
+	}
+	line
+}
+
+fn shell_quote(value: &str) -> String {
+	if !value.is_empty() && value.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,+@%".contains(c)) {
+		value.to_string()
+	} else {
+		format!("'{}'", value.replace('\'', "'\\''"))
+	}
+}
@@ -1,11 +1,12 @@
 use std::fs::{read_to_string, write};
-use std::path::Path;
 use std::time::{Duration, SystemTime};
 use litcrypt2::lc_env;
 use oauth2::basic::{BasicClient, BasicTokenResponse};
 use oauth2::{AuthUrl, ClientId, DeviceAuthorizationUrl, HttpRequest, HttpResponse, RedirectUrl, RefreshToken, Scope, StandardDeviceAuthorizationResponse, TokenResponse, TokenUrl};
 use reqwest::Method;
 use serde::{Deserialize, Serialize};
+use sha1::{Digest, Sha1};
+use crate::backend::data_dir;
 use crate::model::{Account, McCredentials, MsCredentials};
 
 const AUTH_URL: &str = "https://login.microsoftonline.com/consumers/oauth2/v2.0/authorize";
@@ -18,8 +19,13 @@ const XBOX_SECURE_AUTH_URL: &str = "https://xsts.auth.xboxlive.com/xsts/authoriz
 
 const MC_AUTH_URL: &str = "https://api.minecraftservices.com/authentication/login_with_xbox";
 const MC_PROFILE_URL: &str = "https://api.minecraftservices.com/minecraft/profile";
+const MC_ENTITLEMENTS_URL: &str = "https://api.minecraftservices.com/entitlements/mcstore";
 
-pub const ACCOUNT_FILE: &str = "data/account.toml";
+/// Name the game uses for demo players.
+const DEMO_NAME: &str = "Player";
+
+/// Relative to the data directory.
+pub const ACCOUNT_FILE: &str = "account.toml";
 
 const fn env_checker() {
 	env!("CLIENT_ID");
@@ -107,6 +113,24 @@ struct MinecraftProfileResponse {
 	name: String
 }
 
+#[derive(Deserialize)]
+struct MinecraftEntitlement {
+	name: String
+}
+
+#[derive(Deserialize)]
+struct MinecraftEntitlementsResponse {
+	#[serde(default)]
+	items: Vec<MinecraftEntitlement>
+}
+
+/// Name, id and demo flag of an account.
+struct Profile {
+	name: String,
+	id: String,
+	demo: bool
+}
+
 struct XboxResponses {
 	login: XboxLoginResponse,
 	token: XboxSecureTokenResponse
@@ -206,13 +230,60 @@ async fn mc_login(http_client: &reqwest::Client, ms_creds: &MsCredentials)
 	})
 }
 
+async fn mc_has_license(http_client: &reqwest::Client, mc_creds: &McCredentials) -> Result<bool, String> {
+	let res = http_client.request(Method::GET, MC_ENTITLEMENTS_URL)
+		.header("Authorization", format!("Bearer {}", mc_creds.access_token))
+		.send().await.map_err(|err| err.to_string())?
+		.error_for_status().map_err(|err| format!("Failed to check the game license: {}", err))?
+		.bytes().await.map_err(|err| err.to_string())?;
+	let res: MinecraftEntitlementsResponse = serde_json::from_slice(&res)
+		.map_err(|err| format!("Invalid entitlements response: {}", err))?;
+	Ok(res.items.iter().any(|item| item.name == "product_minecraft" || item.name == "game_minecraft"))
+}
+
+/// Returns `None` if the account doesn't have a Minecraft profile.
 async fn mc_get_profile(http_client: &reqwest::Client, mc_creds: &McCredentials)
-	-> Result<MinecraftProfileResponse, reqwest::Error> {
+	-> Result<Option<MinecraftProfileResponse>, String> {
 	let res = http_client.request(Method::GET, MC_PROFILE_URL)
 		.header("Authorization", format!("Bearer {}", mc_creds.access_token))
-		.send().await?.bytes().await?;
-	let res: MinecraftProfileResponse = serde_json::from_slice(&res).unwrap();
-	Ok(res)
+		.send().await.map_err(|err| err.to_string())?;
+	if res.status() == reqwest::StatusCode::NOT_FOUND {
+		return Ok(None);
+	}
+	let res = res.error_for_status().map_err(|err| format!("Failed to get the profile: {}", err))?
+		.bytes().await.map_err(|err| err.to_string())?;
+	let res: MinecraftProfileResponse = serde_json::from_slice(&res)
+		.map_err(|err| format!("Invalid profile response: {}", err))?;
+	Ok(Some(res))
+}
+
+/// Looks up the profile of the account, accounts without a profile and a game license get a
+/// demo profile.
+async fn get_profile(http_client: &reqwest::Client, ms_creds: &MsCredentials, mc_creds: &McCredentials)
+	-> Result<Profile, String> {
+	// the entitlements can be empty for licensed accounts such as Game Pass ones, so a profile wins
+	if let Some(profile) = mc_get_profile(http_client, mc_creds).await? {
+		return Ok(Profile {
+			name: profile.name,
+			id: profile.id,
+			demo: false
+		});
+	}
+
+	if mc_has_license(http_client, mc_creds).await? {
+		Err("The account owns the game but has no profile yet, create one on minecraft.net first".to_string())
+	} else {
+		Ok(Profile {
+			name: DEMO_NAME.to_string(),
+			id: demo_id(&ms_creds.user_hash),
+			demo: true
+		})
+	}
+}
+
+/// Stable offline style id for demo accounts derived from the Xbox user hash.
+fn demo_id(user_hash: &str) -> String {
+	Sha1::digest(user_hash.as_bytes())[..16].iter().map(|byte| format!("{:02x}", byte)).collect()
 }
 
 async fn do_full_login_with_token(http_client: &reqwest::Client, token_res: BasicTokenResponse)
@@ -235,11 +306,12 @@ async fn do_full_login_with_token(http_client: &reqwest::Client, token_res: Basi
 
 	let mc_creds = mc_login(&http_client, &ms_creds).await.map_err(|err| err.to_string())?;
 
-	let mc_profile = mc_get_profile(&http_client, &mc_creds).await.map_err(|err| err.to_string())?;
+	let profile = get_profile(&http_client, &ms_creds, &mc_creds).await?;
 
 	Ok(Account {
-		name: mc_profile.name,
-		id: mc_profile.id,
+		name: profile.name,
+		id: profile.id,
+		demo: profile.demo,
 		ms_creds,
 		mc_creds
 	})
@@ -265,11 +337,12 @@ pub async fn refresh_ms(http_client: reqwest::Client, acc: Account) -> Result<Ac
 pub async fn refresh_mc(http_client: reqwest::Client, mut acc: Account) -> Result<Account, String> {
 	let mc_creds = mc_login(&http_client, &acc.ms_creds).await.map_err(|err| err.to_string())?;
 
-	let mc_profile = mc_get_profile(&http_client, &mc_creds).await.map_err(|err| err.to_string())?;
+	let profile = get_profile(&http_client, &acc.ms_creds, &mc_creds).await?;
 
 	acc.mc_creds = mc_creds;
-	acc.name = mc_profile.name;
-	acc.id = mc_profile.id;
+	acc.name = profile.name;
+	acc.id = profile.id;
+	acc.demo = profile.demo;
 	Ok(acc)
 }
 
@@ -292,7 +365,7 @@ pub async fn finish_code_login(http_client: reqwest::Client, res: StandardDevice
 }
 
 pub fn load_account_from_file() -> Option<Account> {
-	if let Ok(data) = read_to_string(ACCOUNT_FILE) {
+	if let Ok(data) = read_to_string(data_dir().join(ACCOUNT_FILE)) {
 		let acc: Account = toml::from_str(&data).ok()?;
 		Some(acc)
 	} else {
@@ -302,6 +375,6 @@ pub fn load_account_from_file() -> Option<Account> {
 
 pub fn save_account_to_file(account: &Account) -> std::io::Result<()> {
 	let data = toml::to_string(account).unwrap();
-	std::fs::create_dir_all(Path::new(ACCOUNT_FILE).parent().unwrap())?;
-	write(ACCOUNT_FILE, data)
+	std::fs::create_dir_all(data_dir())?;
+	write(data_dir().join(ACCOUNT_FILE), data)
 }
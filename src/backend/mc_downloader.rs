@@ -1,7 +1,23 @@
-use crate::backend::Downloader;
-use crate::model::all_versions;
+use crate::backend::{Downloader, McError, McResult};
+use crate::model::{all_versions, ModLoader};
 
 const VERSIONS_URL: &str = "https://piston-meta.mojang.com/mc/game/version_manifest_v2.json";
+const FABRIC_META_URL: &str = "https://meta.fabricmc.net/v2";
+const QUILT_META_URL: &str = "https://meta.quiltmc.org/v3";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LoaderVersion {
+	pub version: String,
+	pub stable: bool
+}
+
+fn loader_meta_url(loader: ModLoader) -> McResult<&'static str> {
+	match loader {
+		ModLoader::Fabric => Ok(FABRIC_META_URL),
+		ModLoader::Quilt => Ok(QUILT_META_URL),
+		loader => Err(McError::Unsupported(format!("{} versions can't be installed yet", loader)))
+	}
+}
 
 pub struct McDownloader {
 	downloader: Downloader<usize>
@@ -29,4 +45,33 @@ impl McDownloader {
 		let versions: all_versions::Versions = serde_json::from_slice(&data).unwrap();
 		Ok(versions)
 	}
+
+	/// Lists the versions of `loader` that support `game_version`, newest first.
+	pub async fn download_loader_versions(&self, loader: ModLoader, game_version: &str) -> McResult<Vec<LoaderVersion>> {
+		let url = format!("{}/versions/loader/{}", loader_meta_url(loader)?, game_version);
+		let data = self.downloader.download_one(&url).await?;
+		let entries: serde_json::Value = serde_json::from_slice(&data)
+			.map_err(|err| McError::Integrity(format!("invalid {} versions: {}", loader, err)))?;
+		let versions = entries.as_array()
+			.map(|entries| entries.iter()
+				.filter_map(|entry| {
+					let version = entry["loader"]["version"].as_str()?;
+					// quilt doesn't mark stable versions, but names its betas
+					let stable = entry["loader"]["stable"].as_bool().unwrap_or(!version.contains("beta"));
+					Some(LoaderVersion {
+						version: version.to_string(),
+						stable
+					})
+				})
+				.collect())
+			.unwrap_or_default();
+		Ok(versions)
+	}
+
+	/// Downloads the launcher profile of a loader version, a partial version JSON that inherits
+	/// from the vanilla one.
+	pub async fn download_loader_profile(&self, loader: ModLoader, game_version: &str, loader_version: &str) -> McResult<Vec<u8>> {
+		let url = format!("{}/versions/loader/{}/{}/profile/json", loader_meta_url(loader)?, game_version, loader_version);
+		Ok(self.downloader.download_one(&url).await?)
+	}
 }
@@ -1,16 +1,59 @@
 use std::fmt::{Display, Formatter};
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use aho_corasick::AhoCorasick;
-use crate::backend::McDownloader;
-use crate::model::{Account, all_versions};
-
-const VERSIONS_FILE: &str = "data/versions.json";
+use sha1::{Digest, Sha1};
+use crate::backend::{cache_dir, instance_game_dir, LoaderVersion, McDownloader, resolve_env};
+use crate::model::{Account, all_versions, Instance, ModLoader};
+
+/// Replaces secrets in [`LaunchCommand::redacted`].
+pub const REDACTED: &str = "<redacted>";
+// relative to the cache directory
+const VERSIONS_FILE: &str = "versions.json";
+const LOADERS_DIR: &str = "loaders";
 const RESOURCES_URL: &str = "https://resources.download.minecraft.net";
+const LOG_CONFIGS_DIR: &str = "assets/log_configs";
+const CUSTOM_LOG_CONFIG_FILE: &str = "hzlauncher-log4j2.xml";
+/// Quick play log written by the game, relative to the game directory.
+pub const QUICK_PLAY_LOG: &str = "quickPlay/log.json";
+
+/// Log4j2 configuration used instead of the one shipped with the version when
+/// [`LaunchOptions::custom_log_config`] is set. Every event is written to stdout as a single
+/// line of XML so the launcher can parse levels and loggers reliably, while `logs/latest.log`
+/// keeps the usual human readable format. Message lookups are disabled for versions that
+/// would otherwise be vulnerable to Log4Shell.
+const CUSTOM_LOG_CONFIG: &str = r#"<?xml version="1.0" encoding="UTF-8"?>
+<Configuration status="WARN">
+	<Appenders>
+		<Console name="SysOut" target="SYSTEM_OUT">
+			<XMLLayout compact="true" eventEol="true" />
+		</Console>
+		<RollingRandomAccessFile name="File" fileName="logs/latest.log" filePattern="logs/%d{yyyy-MM-dd}-%i.log.gz">
+			<PatternLayout pattern="[%d{HH:mm:ss}] [%t/%level]: %msg{nolookups}%n" />
+			<Policies>
+				<TimeBasedTriggeringPolicy />
+				<OnStartupTriggeringPolicy />
+			</Policies>
+		</RollingRandomAccessFile>
+	</Appenders>
+	<Loggers>
+		<Root level="info">
+			<filters>
+				<MarkerFilter marker="NETWORK_PACKETS" onMatch="DENY" onMismatch="NEUTRAL" />
+			</filters>
+			<AppenderRef ref="SysOut" />
+			<AppenderRef ref="File" />
+		</Root>
+	</Loggers>
+</Configuration>
+"#;
 
 #[derive(Debug, Clone)]
 pub enum McError {
 	Network(String),
-	Fs(String)
+	Fs(String),
+	Integrity(String),
+	Process(String),
+	Unsupported(String)
 }
 
 impl From<reqwest::Error> for McError {
@@ -25,21 +68,273 @@ impl From<tokio::io::Error> for McError {
 	}
 }
 
+impl From<zip::result::ZipError> for McError {
+	fn from(value: zip::result::ZipError) -> Self {
+		match value {
+			zip::result::ZipError::Io(err) => Self::Fs(err.to_string()),
+			err => Self::Integrity(err.to_string())
+		}
+	}
+}
+
 impl Display for McError {
 	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
 		match self {
 			McError::Network(err) => write!(f, "Network error: {}", err),
-			McError::Fs(err) => write!(f, "Filesystem error: {}", err)
+			McError::Fs(err) => write!(f, "Filesystem error: {}", err),
+			McError::Integrity(err) => write!(f, "Integrity error: {}", err),
+			McError::Process(err) => write!(f, "Process error: {}", err),
+			McError::Unsupported(err) => write!(f, "Unsupported: {}", err)
 		}
 	}
 }
 
 pub type McResult<T> = Result<T, McError>;
 
+/// Where the game should go straight after starting.
+#[derive(Debug, Clone, PartialEq)]
+pub enum QuickPlay {
+	/// Name of the world folder in `saves`.
+	Singleplayer(String),
+	/// Server address, optionally with a port.
+	Multiplayer(String),
+	/// Realm id.
+	Realms(String)
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LaunchOptions {
+	/// Use the launcher's own log4j configuration instead of the one from the version JSON.
+	pub custom_log_config: bool,
+	pub quick_play: Option<QuickPlay>
+}
+
+/// Features the `rules` of arguments are checked against.
+#[derive(Debug, Clone, Default)]
+struct Features {
+	is_demo_user: bool,
+	has_custom_resolution: bool,
+	has_quick_plays_support: bool,
+	is_quick_play_singleplayer: bool,
+	is_quick_play_multiplayer: bool,
+	is_quick_play_realms: bool
+}
+
+impl Features {
+	fn get(&self, feature: &str) -> bool {
+		match feature {
+			"is_demo_user" => self.is_demo_user,
+			"has_custom_resolution" => self.has_custom_resolution,
+			"has_quick_plays_support" => self.has_quick_plays_support,
+			"is_quick_play_singleplayer" => self.is_quick_play_singleplayer,
+			"is_quick_play_multiplayer" => self.is_quick_play_multiplayer,
+			"is_quick_play_realms" => self.is_quick_play_realms,
+			_ => false
+		}
+	}
+}
+
+#[derive(Debug, Clone)]
+pub struct VersionDetails {
+	/// Major version of the Java runtime the version is meant to run on.
+	pub java_version: u32,
+	/// Estimated bytes to download for a fresh install, including all assets.
+	pub download_size: u64
+}
+
+/// A version whose files are all in place, see [`McManager::install`].
+pub struct InstalledVersion {
+	/// The version JSON, with the mod loader profile applied.
+	pub version: serde_json::Value,
+	pub classpath: String,
+	pub paths: LaunchPaths
+}
+
+/// Absolute directories the launch arguments point at.
+#[derive(Debug, Clone)]
+pub struct LaunchPaths {
+	pub game_dir: PathBuf,
+	pub assets: PathBuf,
+	pub natives: PathBuf
+}
+
+/// Everything needed to start a prepared version of the game.
+#[derive(Debug, Clone)]
+pub struct LaunchCommand {
+	pub instance_id: String,
+	pub version: String,
+	/// Wrapper program and its arguments the game is started through, may be empty.
+	pub wrapper: Vec<String>,
+	pub program: String,
+	pub args: Vec<String>,
+	pub working_dir: PathBuf,
+	/// Environment variables to set, or to remove if the value is `None`.
+	pub env: Vec<(String, Option<String>)>,
+	/// Values such as the access token that must not be shown or logged.
+	pub secrets: Vec<String>
+}
+
+impl LaunchCommand {
+	/// Builds the process, with `argfile` the arguments are passed through a Java `@argfile`
+	/// written from [`LaunchCommand::argfile_contents`] instead of the command line.
+	pub fn to_command(&self, argfile: Option<&Path>) -> tokio::process::Command {
+		let mut command = match self.wrapper.split_first() {
+			Some((wrapper, wrapper_args)) => {
+				let mut command = tokio::process::Command::new(wrapper);
+				command.args(wrapper_args)
+					.arg(&self.program);
+				command
+			}
+			None => tokio::process::Command::new(&self.program)
+		};
+		match argfile {
+			Some(argfile) => command.arg(format!("@{}", argfile.display())),
+			None => command.args(&self.args)
+		};
+		command.current_dir(&self.working_dir);
+		for (name, value) in &self.env {
+			match value {
+				Some(value) => command.env(name, value),
+				None => command.env_remove(name)
+			};
+		}
+		command
+	}
+
+	/// Contents of a Java `@argfile` holding all arguments, one quoted argument per line.
+	pub fn argfile_contents(&self) -> String {
+		let mut contents = String::new();
+		for arg in &self.args {
+			contents.push('"');
+			contents.push_str(&arg.replace('\\', "\\\\").replace('"', "\\\""));
+			contents.push_str("\"\n");
+		}
+		contents
+	}
+
+	/// Replaces every secret in `text`.
+	pub fn redact(&self, text: &str) -> String {
+		self.secrets.iter()
+			.filter(|secret| !secret.is_empty())
+			.fold(text.to_string(), |text, secret| text.replace(secret.as_str(), REDACTED))
+	}
+
+	/// Returns a copy with every secret replaced, for showing or logging it.
+	pub fn redacted(&self) -> Self {
+		let redact = |value: &String| self.redact(value);
+		Self {
+			instance_id: self.instance_id.clone(),
+			version: self.version.clone(),
+			wrapper: self.wrapper.iter().map(redact).collect(),
+			program: self.program.clone(),
+			args: self.args.iter().map(redact).collect(),
+			working_dir: self.working_dir.clone(),
+			env: self.env.iter().map(|(name, value)| (name.clone(), value.as_ref().map(redact))).collect(),
+			secrets: self.secrets.clone()
+		}
+	}
+}
+
+/// Whether the version JSON and client jar of `version` were downloaded. Libraries and assets
+/// are only checked when launching.
+pub async fn version_installed(version: &str) -> bool {
+	let exists = |path: PathBuf| async move { tokio::fs::try_exists(path).await.is_ok_and(|value| value == true) };
+	exists(cache_dir().join(format!("versions/{}.json", version))).await
+		&& exists(cache_dir().join(format!("clients/{}.jar", version))).await
+}
+
+/// Splits `host:port`, `[ipv6]:port` or a bare host or IPv6 address into host and port, with
+/// the default port when there is none.
+fn split_server_address(server: &str) -> (&str, &str) {
+	const DEFAULT_PORT: &str = "25565";
+	if let Some(rest) = server.strip_prefix('[') {
+		if let Some((host, rest)) = rest.split_once(']') {
+			return (host, rest.strip_prefix(':').filter(|port| !port.is_empty()).unwrap_or(DEFAULT_PORT));
+		}
+	}
+	match server.split_once(':') {
+		// more than one colon is an IPv6 address without a port
+		Some((host, port)) if !port.contains(':') => (host, Some(port).filter(|port| !port.is_empty()).unwrap_or(DEFAULT_PORT)),
+		_ => (server, DEFAULT_PORT)
+	}
+}
+
+/// Turns maven coordinates `group:artifact:version[:classifier]` into the path of the jar in a
+/// maven repository, e.g. `net/fabricmc/fabric-loader/0.15.3/fabric-loader-0.15.3.jar`.
+fn maven_path(name: &str) -> Option<String> {
+	let mut parts = name.split(':');
+	let group = parts.next()?;
+	let artifact = parts.next()?;
+	let version = parts.next()?;
+	let file = match parts.next() {
+		Some(classifier) => format!("{}-{}-{}.jar", artifact, version, classifier),
+		None => format!("{}-{}.jar", artifact, version)
+	};
+	Some(format!("{}/{}/{}/{}", group.replace('.', "/"), artifact, version, file))
+}
+
+/// `group:artifact` of maven coordinates, libraries with the same one are different versions of
+/// the same library.
+fn maven_key(name: &str) -> Option<&str> {
+	let mut separators = name.match_indices(':');
+	separators.next()?;
+	let (end, _) = separators.next()?;
+	Some(&name[..end])
+}
+
+/// Applies a loader profile (Fabric/Quilt style) to a vanilla version JSON: its libraries replace
+/// vanilla ones of the same artifact, its main class is used and its arguments are appended.
+fn merge_loader_profile(version: &mut serde_json::Value, profile: &serde_json::Value) {
+	let loader_libraries: Vec<serde_json::Value> = profile["libraries"].as_array()
+		.map(|libraries| libraries.iter()
+			.filter_map(|library| {
+				let name = library["name"].as_str()?;
+				let path = maven_path(name)?;
+				let repository = library["url"].as_str().unwrap_or("https://repo1.maven.org/maven2/");
+				Some(serde_json::json!({
+					"name": name,
+					"downloads": {
+						"artifact": {
+							"path": path,
+							"url": format!("{}/{}", repository.trim_end_matches('/'), path)
+						}
+					}
+				}))
+			})
+			.collect())
+		.unwrap_or_default();
+	let loader_keys: Vec<&str> = loader_libraries.iter()
+		.filter_map(|library| maven_key(library["name"].as_str().unwrap()))
+		.collect();
+
+	if let Some(libraries) = version["libraries"].as_array_mut() {
+		libraries.retain(|library| library["name"].as_str()
+			.and_then(maven_key)
+			.map_or(true, |key| !loader_keys.contains(&key)));
+		libraries.extend(loader_libraries.iter().cloned());
+	}
+
+	if let Some(main_class) = profile["mainClass"].as_str() {
+		version["mainClass"] = main_class.into();
+	}
+
+	// loaders only support versions with the modern argument format
+	if version.get("arguments").is_some() {
+		for kind in ["jvm", "game"] {
+			if let (Some(arguments), Some(extra)) = (version["arguments"][kind].as_array_mut(), profile["arguments"][kind].as_array()) {
+				arguments.extend(extra.iter().cloned());
+			}
+		}
+	}
+}
+
+pub fn sha1_hex(data: &[u8]) -> String {
+	Sha1::digest(data).iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 pub struct McManager {
 	pub mc_downloader: McDownloader,
 	pub versions: Option<all_versions::Versions>,
-	pub account: Option<Account>,
 	aho: AhoCorasick
 }
 
@@ -58,16 +353,27 @@ impl McManager {
 			"${natives_directory}",
 			"${launcher_name}",
 			"${launcher_version}",
-			"${classpath}"
+			"${classpath}",
+			"${quickPlayPath}",
+			"${quickPlaySingleplayer}",
+			"${quickPlayMultiplayer}",
+			"${quickPlayRealms}",
+			"${user_properties}",
+			"${auth_session}",
+			"${game_assets}",
+			"${resolution_width}",
+			"${resolution_height}"
 		];
 		let aho = AhoCorasick::new(patterns).unwrap();
-		Self { mc_downloader, versions: None, account: None, aho }
+		Self { mc_downloader, versions: None, aho }
 	}
 
-	pub async fn load_versions(&mut self) -> McResult<()> {
-		match tokio::fs::read_to_string(VERSIONS_FILE).await {
+	pub async fn load_versions(&mut self) -> McResult<all_versions::Versions> {
+		let versions_file = cache_dir().join(VERSIONS_FILE);
+		match tokio::fs::read_to_string(&versions_file).await {
 			Ok(data) => {
-				if let Ok(versions) = serde_json::from_str(&data) {
+				if let Ok(versions) = serde_json::from_str::<all_versions::Versions>(&data) {
+					self.versions = Some(versions.clone());
 					return Ok(versions);
 				}
 			}
@@ -78,13 +384,13 @@ impl McManager {
 		}
 
 		let versions = self.mc_downloader.download_versions().await?;
-		tokio::fs::create_dir_all(Path::new(VERSIONS_FILE).parent().unwrap()).await?;
-		tokio::fs::write(VERSIONS_FILE, serde_json::to_string(&versions).unwrap()).await?;
-		self.versions = Some(versions);
-		Ok(())
+		tokio::fs::create_dir_all(cache_dir()).await?;
+		tokio::fs::write(&versions_file, serde_json::to_string(&versions).unwrap()).await?;
+		self.versions = Some(versions.clone());
+		Ok(versions)
 	}
 
-	fn check_rules(rules: Option<&serde_json::Value>) -> bool {
+	fn check_rules(rules: Option<&serde_json::Value>, features: &Features) -> bool {
 		let rules = match rules {
 			Some(rules) => rules,
 			None => return true
@@ -110,20 +416,9 @@ impl McManager {
 					true
 				};
 				is_name && is_arch
-			} else if let Some(features) = rule.get("features") {
-				let mut is = true;
-				for (feature, value) in features.as_object().unwrap() {
-					let value = value.as_bool().unwrap();
-					if feature == "has_custom_resolution" && value == true {
-						// todo
-						is = false;
-						break;
-					} else {
-						is = false;
-						break;
-					}
-				}
-				is
+			} else if let Some(required) = rule.get("features") {
+				required.as_object().unwrap().iter()
+					.all(|(feature, value)| features.get(feature) == value.as_bool().unwrap())
 			} else {
 				todo!()
 			};
@@ -134,28 +429,56 @@ impl McManager {
 		allow
 	}
 
-	fn do_replacements(&self, argument: &str, version: &serde_json::Value, classpath: &str) -> String {
-		let acc = self.account.as_ref().unwrap();
-
-		let instance_path = Path::new("data/instance").canonicalize().unwrap();
-		let assets_path = Path::new("data/assets").canonicalize().unwrap();
-		let natives_path = Path::new("data/natives").canonicalize().unwrap();
-
-		let replace = &[
-			acc.name.as_str(),
-			version["id"].as_str().unwrap(),
-			instance_path.to_str().unwrap(),
-			assets_path.to_str().unwrap(),
-			version["assets"].as_str().unwrap(),
-			acc.id.as_str(),
-			acc.mc_creds.access_token.as_str(),
-			"mojang",
-			version["type"].as_str().unwrap(),
-			natives_path.to_str().unwrap(),
-			"HZLauncher",
-			"1.0",
-			classpath
-		];
+	/// Values for the placeholders of [`McManager::new`], in the same order.
+	fn replacements(
+		version: &serde_json::Value,
+		classpath: &str,
+		paths: &LaunchPaths,
+		acc: &Account,
+		quick_play: Option<&QuickPlay>,
+		resolution: Option<(u32, u32)>) -> Vec<String> {
+		let instance_path = &paths.game_dir;
+		let assets_path = &paths.assets;
+		let natives_path = &paths.natives;
+		let quick_play_path = instance_path.join(QUICK_PLAY_LOG);
+		let legacy_assets_path = assets_path.join("virtual/legacy");
+
+		let (singleplayer, multiplayer, realms) = match quick_play {
+			Some(QuickPlay::Singleplayer(world)) => (world.as_str(), "", ""),
+			Some(QuickPlay::Multiplayer(server)) => ("", server.as_str(), ""),
+			Some(QuickPlay::Realms(realm)) => ("", "", realm.as_str()),
+			None => ("", "", "")
+		};
+		// only used when `has_custom_resolution` is enabled, the default is the game's own
+		let (width, height) = resolution.unwrap_or((854, 480));
+
+		vec![
+			acc.name.clone(),
+			version["id"].as_str().unwrap().to_string(),
+			instance_path.to_str().unwrap().to_string(),
+			assets_path.to_str().unwrap().to_string(),
+			version["assets"].as_str().unwrap().to_string(),
+			acc.id.clone(),
+			acc.mc_creds.access_token.clone(),
+			"mojang".to_string(),
+			version["type"].as_str().unwrap().to_string(),
+			natives_path.to_str().unwrap().to_string(),
+			"HZLauncher".to_string(),
+			"1.0".to_string(),
+			classpath.to_string(),
+			quick_play_path.to_str().unwrap().to_string(),
+			singleplayer.to_string(),
+			multiplayer.to_string(),
+			realms.to_string(),
+			"{}".to_string(),
+			acc.mc_creds.access_token.clone(),
+			legacy_assets_path.to_str().unwrap().to_string(),
+			width.to_string(),
+			height.to_string()
+		]
+	}
+
+	fn do_replacements(&self, argument: &str, replace: &[String]) -> String {
 		let res = self.aho.replace_all(argument, replace);
 		if res.starts_with('$') {
 			String::new()
@@ -164,50 +487,198 @@ impl McManager {
 		}
 	}
 
-	pub async fn play_version(&mut self, version: &str) -> McResult<()> {
-		assert!(self.account.is_some());
-
-		let file_path = format!("data/versions/{}.json", version);
-		let version: serde_json::Value = match tokio::fs::read_to_string(&file_path).await {
-			Ok(data) => {
-				serde_json::from_str(&data).unwrap()
+	/// Appends the arguments from a `jvm` or `game` list of the version JSON whose rules match.
+	fn add_arguments(&self, arguments: &serde_json::Value, features: &Features, replace: &[String], out: &mut Vec<String>) {
+		for argument in arguments.as_array().unwrap() {
+			if !Self::check_rules(argument.get("rules"), features) {
+				continue;
 			}
-			Err(err) if err.kind() != tokio::io::ErrorKind::NotFound => {
-				return Err(McError::from(err));
+			let value = if let Some(value) = argument.get("value") {
+				value
+			} else {
+				argument
+			};
+			if let Some(single) = value.as_str() {
+				out.push(self.do_replacements(single, replace));
+			} else if let Some(multiple) = value.as_array() {
+				for argument in multiple {
+					out.push(self.do_replacements(argument.as_str().unwrap(), replace));
+				}
+			} else {
+				unreachable!();
+			}
+		}
+	}
+
+	/// Makes sure the log4j configuration for `version` is present in [`LOG_CONFIGS_DIR`] and
+	/// returns the JVM argument that points the game at it, if the version uses one.
+	async fn install_log_config(&self, version: &serde_json::Value, options: &LaunchOptions) -> McResult<Option<String>> {
+		let logging = &version["logging"]["client"];
+		let argument = match logging["argument"].as_str() {
+			Some(argument) => argument,
+			None => return Ok(None)
+		};
+
+		let log_configs_dir = cache_dir().join(LOG_CONFIGS_DIR);
+		tokio::fs::create_dir_all(&log_configs_dir).await?;
+
+		let path = if options.custom_log_config {
+			let path = log_configs_dir.join(CUSTOM_LOG_CONFIG_FILE);
+			tokio::fs::write(&path, CUSTOM_LOG_CONFIG).await?;
+			path
+		} else {
+			let file = &logging["file"];
+			let path = log_configs_dir.join(file["id"].as_str().unwrap());
+			let sha1 = file["sha1"].as_str().unwrap();
+
+			let up_to_date = match tokio::fs::read(&path).await {
+				Ok(data) => sha1_hex(&data) == sha1,
+				Err(_) => false
+			};
+			if !up_to_date {
+				let data = self.mc_downloader.download_one(file["url"].as_str().unwrap()).await?;
+				let hash = sha1_hex(&data);
+				if hash != sha1 {
+					return Err(McError::Integrity(format!("{} has sha1 {}, expected {}", path.display(), hash, sha1)));
+				}
+				tokio::fs::write(&path, data).await?;
 			}
+			path
+		};
+
+		let path = path.canonicalize()?;
+		Ok(Some(argument.replace("${path}", path.to_str().unwrap())))
+	}
+
+	/// Returns the version JSON of `id`, downloading it if it isn't cached yet.
+	async fn load_version(&mut self, id: &str) -> McResult<serde_json::Value> {
+		let file_path = cache_dir().join(format!("versions/{}.json", id));
+		match tokio::fs::read_to_string(&file_path).await {
+			Ok(data) => Ok(serde_json::from_str(&data).unwrap()),
+			Err(err) if err.kind() != tokio::io::ErrorKind::NotFound => Err(McError::from(err)),
 			_ => {
-				let url = &self.versions.as_ref().unwrap().versions.iter().find(|v| v.id == version).unwrap().url;
+				if self.versions.is_none() {
+					self.load_versions().await?;
+				}
+				let url = &self.versions.as_ref().unwrap().versions.iter().find(|v| v.id == id)
+					.ok_or_else(|| McError::Unsupported(format!("unknown version {}", id)))?
+					.url;
 				let data_vec = self.mc_downloader.download_one(url).await?;
 				let data: serde_json::Value = serde_json::from_slice(&data_vec).unwrap();
-				tokio::fs::create_dir_all(Path::new(&file_path).parent().unwrap()).await?;
+				tokio::fs::create_dir_all(file_path.parent().unwrap()).await?;
 				tokio::fs::write(&file_path, data_vec).await?;
-				data
+				Ok(data)
+			}
+		}
+	}
+
+	/// Details of a version that are only known from its version JSON.
+	pub async fn version_details(&mut self, id: &str) -> McResult<VersionDetails> {
+		let version = self.load_version(id).await?;
+		let libraries_size: u64 = version["libraries"].as_array()
+			.map(|libraries| libraries.iter()
+				.filter(|library| Self::check_rules(library.get("rules"), &Features::default()))
+				.filter_map(|library| library["downloads"]["artifact"]["size"].as_u64())
+				.sum())
+			.unwrap_or(0);
+		let client_size = version["downloads"]["client"]["size"].as_u64().unwrap_or(0);
+		let assets_size = version["assetIndex"]["totalSize"].as_u64().unwrap_or(0);
+		Ok(VersionDetails {
+			// versions before 1.17 don't say, they all run on Java 8
+			java_version: version["javaVersion"]["majorVersion"].as_u64().unwrap_or(8) as u32,
+			download_size: libraries_size + client_size + assets_size
+		})
+	}
+
+	/// Lists the versions of `loader` that support the game version `game_version`, newest first.
+	pub async fn loader_versions(&self, loader: ModLoader, game_version: &str) -> McResult<Vec<LoaderVersion>> {
+		self.mc_downloader.download_loader_versions(loader, game_version).await
+	}
+
+	/// Returns the launcher profile of the mod loader of `instance`, downloading it if it isn't
+	/// cached yet. Without a loader version the newest stable one is used.
+	async fn load_loader_profile(&self, instance: &Instance) -> McResult<serde_json::Value> {
+		let loader_version = match &instance.loader_version {
+			Some(loader_version) => loader_version.clone(),
+			None => {
+				let versions = self.mc_downloader.download_loader_versions(instance.loader, &instance.version).await?;
+				versions.iter().find(|version| version.stable).or(versions.first())
+					.ok_or_else(|| McError::Unsupported(format!("{} doesn't support {}", instance.loader, instance.version)))?
+					.version.clone()
 			}
 		};
 
+		let file_path = cache_dir()
+			.join(LOADERS_DIR)
+			.join(instance.loader.id())
+			.join(format!("{}-{}.json", instance.version, loader_version));
+		if let Ok(data) = tokio::fs::read_to_string(&file_path).await {
+			if let Ok(profile) = serde_json::from_str(&data) {
+				return Ok(profile);
+			}
+		}
+
+		let data = self.mc_downloader.download_loader_profile(instance.loader, &instance.version, &loader_version).await?;
+		let profile: serde_json::Value = serde_json::from_slice(&data)
+			.map_err(|err| McError::Integrity(format!(
+				"invalid {} {} profile for {}: {}", instance.loader, loader_version, instance.version, err
+			)))?;
+		tokio::fs::create_dir_all(file_path.parent().unwrap()).await?;
+		tokio::fs::write(&file_path, data).await?;
+		Ok(profile)
+	}
+
+	/// Installs the version of `instance` and builds the command that runs it with `account`.
+	/// Spawning the game is left to the caller so the manager doesn't have to stay locked while
+	/// the game runs.
+	pub async fn prepare_launch(
+		&mut self,
+		instance: &Instance,
+		account: &Account,
+		options: &LaunchOptions) -> McResult<LaunchCommand> {
+		let installed = self.install(instance).await?;
+		let log_config_argument = self.install_log_config(&installed.version, options).await?;
+		self.assemble_launch(&installed.version, &installed.classpath, log_config_argument, &installed.paths, account, instance, options)
+	}
+
+	/// Downloads the version, libraries and assets `instance` needs that aren't there yet.
+	pub async fn install(&mut self, instance: &Instance) -> McResult<InstalledVersion> {
+		if instance.version.is_empty() {
+			return Err(McError::Unsupported(format!("{} doesn't have a Minecraft version set", instance.name)));
+		}
+		let game_dir = instance_game_dir(instance);
+
+		let mut version = self.load_version(&instance.version).await?;
+		if instance.loader != ModLoader::Vanilla {
+			let profile = self.load_loader_profile(instance).await?;
+			merge_loader_profile(&mut version, &profile);
+		}
+
 		let mut id = 0;
 		let libraries = version["libraries"].as_array().unwrap();
 		let mut paths = Vec::with_capacity(libraries.len());
 		let mut classpath = String::new();
+		let cache = cache_dir();
+		let libraries_path = cache.join("libraries");
 		for library in libraries {
 			let artifact = &library["downloads"]["artifact"];
 			let path = artifact["path"].as_str().unwrap();
 			let url = artifact["url"].as_str().unwrap();
-			let allow = Self::check_rules(library.get("rules"));
+			let allow = Self::check_rules(library.get("rules"), &Features::default());
 
 			if !allow {
 				continue;
 			}
 
-			classpath += "data/libraries/";
+			// the game runs inside the instance directory, so the classpath has to be absolute
+			classpath += libraries_path.join(path).to_str().unwrap();
 			if std::env::consts::FAMILY == "unix" {
-				classpath += path;
 				classpath.push(':');
 			} else {
 				classpath.push(';');
 			}
 
-			if tokio::fs::try_exists(format!("data/libraries/{}", path)).await.is_ok_and(|value| value == true) {
+			if tokio::fs::try_exists(libraries_path.join(path)).await.is_ok_and(|value| value == true) {
 				continue;
 			}
 
@@ -225,29 +696,30 @@ impl McManager {
 				return Err(McError::from(result.unwrap_err()));
 			}
 
-			let full_path = format!("data/libraries/{}", path);
-			tokio::fs::create_dir_all(Path::new(&full_path).parent().unwrap()).await?;
+			let full_path = libraries_path.join(path);
+			tokio::fs::create_dir_all(full_path.parent().unwrap()).await?;
 			tokio::fs::write(full_path, result.unwrap()).await?;
 		}
 
-		tokio::fs::create_dir_all("data/clients").await?;
-		let client_file = format!("data/clients/{}.jar", version["id"].as_str().unwrap());
+		tokio::fs::create_dir_all(cache.join("clients")).await?;
+		let client_file = cache.join(format!("clients/{}.jar", version["id"].as_str().unwrap()));
 		if !tokio::fs::try_exists(&client_file).await.is_ok_and(|value| value == true) {
 			let client_url = version["downloads"]["client"]["url"].as_str().unwrap();
 			let client_data = self.mc_downloader.download_one(client_url).await?;
 			tokio::fs::write(&client_file, client_data).await?;
 		}
 
-		classpath += Path::new(&client_file).canonicalize().unwrap().to_str().unwrap();
+		classpath += client_file.canonicalize().unwrap().to_str().unwrap();
 
-		tokio::fs::create_dir_all("data/natives").await?;
-		tokio::fs::create_dir_all("data/instance").await?;
-		tokio::fs::create_dir_all("data/assets/objects").await?;
-		tokio::fs::create_dir_all("data/assets/indexes").await?;
-		tokio::fs::create_dir_all("data/assets/virtual/legacy").await?;
+		let assets_path = cache.join("assets");
+		tokio::fs::create_dir_all(cache.join("natives")).await?;
+		tokio::fs::create_dir_all(&game_dir).await?;
+		tokio::fs::create_dir_all(assets_path.join("objects")).await?;
+		tokio::fs::create_dir_all(assets_path.join("indexes")).await?;
+		tokio::fs::create_dir_all(assets_path.join("virtual/legacy")).await?;
 
 		let asset_index = &version["assetIndex"];
-		let asset_index_file = format!("data/assets/indexes/{}.json", asset_index["id"].as_str().unwrap());
+		let asset_index_file = assets_path.join(format!("indexes/{}.json", asset_index["id"].as_str().unwrap()));
 		let asset_index: serde_json::Value = match tokio::fs::read_to_string(&asset_index_file).await {
 			Ok(data) => serde_json::from_str(&data).unwrap(),
 			_ => {
@@ -263,8 +735,8 @@ impl McManager {
 		for (legacy_path, object) in asset_index["objects"].as_object().unwrap() {
 			let hash = object["hash"].as_str().unwrap();
 			let sub_path = format!("{}/{}", &hash[0..2], hash);
-			let path = format!("data/assets/objects/{}", sub_path);
-			let legacy_path = format!("data/assets/virtual/legacy/{}", legacy_path);
+			let path = assets_path.join("objects").join(&sub_path);
+			let legacy_path = assets_path.join("virtual/legacy").join(legacy_path);
 
 			if tokio::fs::try_exists(&path).await.is_ok_and(|value| value == true) &&
 				tokio::fs::try_exists(&legacy_path).await.is_ok_and(|value| value == true) {
@@ -286,66 +758,114 @@ impl McManager {
 				return Err(McError::from(result.unwrap_err()));
 			}
 
-			let full_path = format!("data/assets/objects/{}", sub_path);
-			tokio::fs::create_dir_all(Path::new(&full_path).parent().unwrap()).await?;
-			tokio::fs::create_dir_all(Path::new(&legacy_path).parent().unwrap()).await?;
+			let full_path = assets_path.join("objects").join(sub_path);
+			tokio::fs::create_dir_all(full_path.parent().unwrap()).await?;
+			tokio::fs::create_dir_all(legacy_path.parent().unwrap()).await?;
 			tokio::fs::write(&full_path, result.unwrap()).await?;
 			tokio::fs::copy(full_path, legacy_path).await?;
 		}
 
+		let paths = LaunchPaths {
+			game_dir: game_dir.canonicalize()?,
+			assets: assets_path.canonicalize()?,
+			natives: cache.join("natives").canonicalize()?
+		};
+		Ok(InstalledVersion { version, classpath, paths })
+	}
+
+	/// Builds the command line for an installed `version` without touching the file system, so it
+	/// can also be used to show what would be launched.
+	pub fn assemble_launch(
+		&self,
+		version: &serde_json::Value,
+		classpath: &str,
+		log_config_argument: Option<String>,
+		paths: &LaunchPaths,
+		account: &Account,
+		instance: &Instance,
+		options: &LaunchOptions) -> McResult<LaunchCommand> {
+		let settings = &instance.settings;
+		let quick_play = options.quick_play.as_ref();
+		let resolution = settings.resolution();
+		let features = Features {
+			is_demo_user: account.demo,
+			has_custom_resolution: resolution.is_some(),
+			has_quick_plays_support: true,
+			is_quick_play_singleplayer: matches!(quick_play, Some(QuickPlay::Singleplayer(_))),
+			is_quick_play_multiplayer: matches!(quick_play, Some(QuickPlay::Multiplayer(_))),
+			is_quick_play_realms: matches!(quick_play, Some(QuickPlay::Realms(_))),
+			..Features::default()
+		};
+		let replace = Self::replacements(version, classpath, paths, account, quick_play, resolution);
+
 		let mut final_arguments = Vec::new();
 
-		let arguments = &version["arguments"];
-		for argument in arguments["jvm"].as_array().unwrap() {
-			if !Self::check_rules(argument.get("rules")) {
-				continue;
-			}
-			let value = if let Some(value) = argument.get("value") {
-				value
-			} else {
-				argument
-			};
-			if let Some(single) = value.as_str() {
-				final_arguments.push(self.do_replacements(single, &version, &classpath));
-			} else if let Some(multiple) = value.as_array() {
-				for argument in multiple {
-					final_arguments.push(self.do_replacements(argument.as_str().unwrap(), &version, &classpath));
+		// versions before 1.13 only have a plain `minecraftArguments` string
+		let arguments = version.get("arguments");
+		match arguments {
+			Some(arguments) => self.add_arguments(&arguments["jvm"], &features, &replace, &mut final_arguments),
+			None => {
+				for argument in ["-Djava.library.path=${natives_directory}", "-cp", "${classpath}"] {
+					final_arguments.push(self.do_replacements(argument, &replace));
 				}
-			} else {
-				unreachable!();
 			}
 		}
 
+		if let Some(jvm_args) = &settings.jvm_args {
+			final_arguments.extend(jvm_args.split_whitespace().map(str::to_string));
+		}
+		if let Some(argument) = log_config_argument {
+			final_arguments.push(argument);
+		}
+
 		final_arguments.push(version["mainClass"].as_str().unwrap().to_string());
 
-		for argument in arguments["game"].as_array().unwrap() {
-			if !Self::check_rules(argument.get("rules")) {
-				continue;
+		match arguments {
+			Some(arguments) => self.add_arguments(&arguments["game"], &features, &replace, &mut final_arguments),
+			None => {
+				for argument in version["minecraftArguments"].as_str().unwrap().split_whitespace() {
+					final_arguments.push(self.do_replacements(argument, &replace));
+				}
+				if account.demo {
+					final_arguments.push("--demo".to_string());
+				}
+				if let Some((width, height)) = resolution {
+					final_arguments.extend(["--width".to_string(), width.to_string(), "--height".to_string(), height.to_string()]);
+				}
 			}
+		}
+		// no version has an argument for it, but every version understands the flag
+		if settings.fullscreen {
+			final_arguments.push("--fullscreen".to_string());
+		}
 
-			let value = if let Some(value) = argument.get("value") {
-				value
-			} else {
-				argument
-			};
-			if let Some(single) = value.as_str() {
-				final_arguments.push(self.do_replacements(single, &version, &classpath));
-			} else if let Some(multiple) = value.as_array() {
-				for argument in multiple {
-					final_arguments.push(self.do_replacements(argument.as_str().unwrap(), &version, &classpath));
+		// older versions don't know quick play, but can still join a server directly
+		let supports_quick_play = arguments.is_some_and(|arguments| arguments["game"].to_string().contains("${quickPlayMultiplayer}"));
+		if !supports_quick_play {
+			match quick_play {
+				Some(QuickPlay::Multiplayer(server)) => {
+					let (host, port) = split_server_address(server);
+					final_arguments.extend(["--server".to_string(), host.to_string(), "--port".to_string(), port.to_string()]);
 				}
-			} else {
-				unreachable!();
+				Some(QuickPlay::Singleplayer(_)) | Some(QuickPlay::Realms(_)) => {
+					return Err(McError::Unsupported(format!("{} doesn't support quick play", version["id"].as_str().unwrap())));
+				}
+				None => {}
 			}
 		}
 
-		let status = tokio::process::Command::new("java")
-			.args(final_arguments)
-			.spawn()
-			.unwrap()
-			.wait().await;
-		eprintln!("java exited with {:?}", status);
-
-		Ok(())
+		Ok(LaunchCommand {
+			instance_id: instance.id.clone(),
+			version: version["id"].as_str().unwrap().to_string(),
+			// the wrapper is split on whitespace, quoting isn't supported
+			wrapper: settings.wrapper_command.as_deref()
+				.map(|wrapper| wrapper.split_whitespace().map(str::to_string).collect())
+				.unwrap_or_default(),
+			program: settings.java_path.clone().unwrap_or_else(|| "java".to_string()),
+			args: final_arguments,
+			working_dir: paths.game_dir.clone(),
+			env: resolve_env(&settings.env),
+			secrets: vec![account.mc_creds.access_token.clone()]
+		})
 	}
 }
@@ -1,5 +1,6 @@
 #![deny(rust_2018_idioms)]
 
+use std::path::PathBuf;
 use iced::{Application, Font, Pixels, Settings, window};
 use litcrypt2::use_litcrypt;
 use crate::ui::Ui;
@@ -10,7 +11,35 @@ mod model;
 
 use_litcrypt!();
 
+/// Prints the command that would launch an instance instead of starting the launcher. The game
+/// isn't started, but the version, libraries and assets it needs are still downloaded if missing,
+/// since the command refers to them.
+const PRINT_LAUNCH_COMMAND_FLAGS: &[&str] = &["--print-launch-command", "--dry-run"];
+
 fn main() -> iced::Result {
+	let args: Vec<String> = std::env::args().skip(1).collect();
+	let dirs = backend::init_dirs(backend::DirOverrides {
+		data: flag_value(&args, backend::DATA_DIR_FLAG),
+		cache: flag_value(&args, backend::CACHE_DIR_FLAG)
+	});
+	// stderr, stdout may be a printed launch command
+	match backend::migrate_legacy_data() {
+		Ok(moved) if !moved.is_empty() => eprintln!(
+			"Moved {} from ./data to {} and {}", moved.join(", "), dirs.data.display(), dirs.cache.display()
+		),
+		Ok(_) => {}
+		Err(err) => eprintln!("error: failed to move ./data to {}: {}", dirs.data.display(), err)
+	}
+
+	if let Some(index) = args.iter().position(|arg| PRINT_LAUNCH_COMMAND_FLAGS.contains(&arg.as_str())) {
+		let instance = args.get(index + 1).filter(|arg| !arg.starts_with("--")).cloned();
+		if let Err(err) = print_launch_command(instance) {
+			eprintln!("error: {}", err);
+			std::process::exit(1);
+		}
+		return Ok(());
+	}
+
 	let settings = Settings {
 		id: None,
 		window: window::Settings::default(),
@@ -22,3 +51,40 @@ fn main() -> iced::Result {
 	};
 	Ui::run(settings)
 }
+
+/// The value of a `--flag value` or `--flag=value` argument.
+fn flag_value(args: &[String], flag: &str) -> Option<PathBuf> {
+	args.iter().enumerate().find_map(|(index, arg)| match arg.strip_prefix(flag) {
+		Some("") => args.get(index + 1).map(PathBuf::from),
+		Some(value) => value.strip_prefix('=').map(PathBuf::from),
+		None => None
+	})
+}
+
+/// Prints the launch command of the instance with the given id, or of the first instance.
+/// Missing game files are downloaded, see [`PRINT_LAUNCH_COMMAND_FLAGS`].
+fn print_launch_command(instance_id: Option<String>) -> Result<(), String> {
+	let account = backend::load_account_from_file()
+		.ok_or_else(|| "no saved account, log in through the launcher first".to_string())?;
+	let runtime = tokio::runtime::Builder::new_current_thread()
+		.enable_all()
+		.build()
+		.map_err(|err| err.to_string())?;
+
+	let launch = runtime.block_on(async {
+		if let Err(err) = backend::migrate_legacy_instance().await {
+			eprintln!("error: failed to migrate the old instance: {}", err);
+		}
+		let instance = match instance_id {
+			Some(id) => backend::load_instance(&id).await?,
+			None => backend::list_instances().await?.into_iter().next()
+				.ok_or_else(|| backend::McError::Unsupported("there are no instances".to_string()))?
+		};
+		let mut manager = backend::McManager::new(backend::McDownloader::new(reqwest::Client::new()));
+		manager.load_versions().await?;
+		manager.prepare_launch(&instance, &account, &backend::LaunchOptions::default()).await
+	}).map_err(|err| err.to_string())?;
+
+	println!("{}", backend::command_line(&launch.redacted()));
+	Ok(())
+}
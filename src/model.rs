@@ -1,4 +1,12 @@
 mod account;
+mod history;
+mod instance;
+mod launcher_config;
+mod quick_play;
 pub mod all_versions;
 
 pub use account::*;
+pub use history::*;
+pub use instance::*;
+pub use launcher_config::*;
+pub use quick_play::*;
@@ -21,6 +21,9 @@ pub struct McCredentials {
 pub struct Account {
 	pub name: String,
 	pub id: String,
+	/// The account doesn't own the game and can only play the demo.
+	#[serde(default)]
+	pub demo: bool,
 	pub ms_creds: MsCredentials,
 	pub mc_creds: McCredentials
 }
@@ -0,0 +1,27 @@
+use std::time::Duration;
+use serde::{Deserialize, Serialize};
+
+/// One launch of an instance, times are milliseconds since the unix epoch.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LaunchRecord {
+	pub started_at: u64,
+	/// `None` while the game is running or if the launcher quit before it.
+	pub ended_at: Option<u64>,
+	pub exit_code: Option<i32>,
+	pub account: String,
+	pub version: String,
+	pub loader: String
+}
+
+impl LaunchRecord {
+	pub fn playtime(&self) -> Duration {
+		match self.ended_at {
+			Some(ended_at) => Duration::from_millis(ended_at.saturating_sub(self.started_at)),
+			None => Duration::ZERO
+		}
+	}
+}
+
+pub fn total_playtime(history: &[LaunchRecord]) -> Duration {
+	history.iter().map(LaunchRecord::playtime).sum()
+}
@@ -0,0 +1,158 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EnvAction {
+	Set,
+	Unset,
+	/// Prepends the value to the launcher's value of a list variable such as `PATH` or `LD_PRELOAD`.
+	Prepend
+}
+
+impl EnvAction {
+	pub const ALL: [EnvAction; 3] = [EnvAction::Set, EnvAction::Unset, EnvAction::Prepend];
+}
+
+impl Display for EnvAction {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			EnvAction::Set => write!(f, "Set"),
+			EnvAction::Unset => write!(f, "Unset"),
+			EnvAction::Prepend => write!(f, "Prepend")
+		}
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvVar {
+	pub name: String,
+	pub action: EnvAction,
+	#[serde(default)]
+	pub value: String
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct InstanceSettings {
+	/// Command prefix the game is started through, for example `gamemoderun` or `nice -n 5`.
+	pub wrapper_command: Option<String>,
+	/// Shell command run before launching, a non-zero exit code aborts the launch.
+	pub pre_launch_command: Option<String>,
+	/// Shell command run after the game exited.
+	pub post_exit_command: Option<String>,
+	/// Environment changes applied to the game process, in order.
+	pub env: Vec<EnvVar>,
+	/// Initial window size, only used when both are set.
+	pub width: Option<u32>,
+	pub height: Option<u32>,
+	pub fullscreen: bool,
+	/// Java executable the game runs on, `java` from `PATH` when unset.
+	pub java_path: Option<String>,
+	/// Extra JVM arguments such as `-Xmx4G`, split on whitespace.
+	pub jvm_args: Option<String>,
+	/// Game directory outside of the instance directory, e.g. one shared with another launcher.
+	pub game_dir: Option<PathBuf>
+}
+
+impl InstanceSettings {
+	pub fn resolution(&self) -> Option<(u32, u32)> {
+		self.width.zip(self.height)
+	}
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModLoader {
+	#[default]
+	Vanilla,
+	Fabric,
+	Quilt,
+	Forge,
+	NeoForge
+}
+
+impl ModLoader {
+	pub const ALL: [ModLoader; 5] = [ModLoader::Vanilla, ModLoader::Fabric, ModLoader::Quilt, ModLoader::Forge, ModLoader::NeoForge];
+
+	pub fn id(&self) -> &'static str {
+		match self {
+			ModLoader::Vanilla => "vanilla",
+			ModLoader::Fabric => "fabric",
+			ModLoader::Quilt => "quilt",
+			ModLoader::Forge => "forge",
+			ModLoader::NeoForge => "neoforge"
+		}
+	}
+}
+
+impl Display for ModLoader {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ModLoader::Vanilla => write!(f, "Vanilla"),
+			ModLoader::Fabric => write!(f, "Fabric"),
+			ModLoader::Quilt => write!(f, "Quilt"),
+			ModLoader::Forge => write!(f, "Forge"),
+			ModLoader::NeoForge => write!(f, "NeoForge")
+		}
+	}
+}
+
+/// Where a mod was downloaded from, so it can be looked up again.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "platform", rename_all = "lowercase")]
+pub enum ModSource {
+	CurseForge {
+		project_id: u64,
+		file_id: u64
+	},
+	Modrinth {
+		project_id: String,
+		version_id: String
+	}
+}
+
+/// A mod in the `mods` folder of an instance.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InstalledMod {
+	/// File name inside the `mods` folder.
+	pub file: String,
+	pub name: String,
+	#[serde(default)]
+	pub disabled: bool,
+	#[serde(default)]
+	pub source: Option<ModSource>
+}
+
+/// An instance as stored in `instances/<id>/instance.toml` in the data directory.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Instance {
+	/// Name of the instance directory, not stored in the file itself.
+	#[serde(skip)]
+	pub id: String,
+	pub name: String,
+	/// Minecraft version id, e.g. `1.20.4`.
+	pub version: String,
+	#[serde(default)]
+	pub loader: ModLoader,
+	/// Version of the mod loader, `None` for vanilla or the latest one.
+	#[serde(default)]
+	pub loader_version: Option<String>,
+	/// Name of the group the instance is listed under, ungrouped when `None`.
+	#[serde(default)]
+	pub group: Option<String>,
+	/// File name of the icon inside the instance directory.
+	#[serde(default)]
+	pub icon: Option<String>,
+	#[serde(default)]
+	pub notes: String,
+	/// Favourites are listed first in their group.
+	#[serde(default)]
+	pub favourite: bool,
+	#[serde(default)]
+	pub settings: InstanceSettings,
+	/// Mods whose origin is known, e.g. from the launcher the instance was imported from.
+	#[serde(default, skip_serializing_if = "Vec::is_empty")]
+	pub mods: Vec<InstalledMod>
+}
@@ -0,0 +1,15 @@
+use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+
+/// Launcher wide settings from `launcher.toml` in the config directory.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LauncherConfig {
+	/// Where accounts, instances and logs are kept, relative paths are relative to the config
+	/// directory.
+	#[serde(default)]
+	pub data_dir: Option<PathBuf>,
+	/// Where versions, libraries and assets are kept, `cache` in the data directory when only
+	/// that is set.
+	#[serde(default)]
+	pub cache_dir: Option<PathBuf>
+}
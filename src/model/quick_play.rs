@@ -0,0 +1,17 @@
+use serde::{Deserialize, Serialize};
+
+/// An entry of the quick play log the game writes to `quickPlayPath`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickPlayEntry {
+	#[serde(default)]
+	pub id: String,
+	#[serde(default)]
+	pub name: String,
+	/// `singleplayer`, `multiplayer` or `realms`.
+	#[serde(rename = "type", default)]
+	pub t: String,
+	#[serde(rename = "gamemode", default)]
+	pub game_mode: Option<String>,
+	#[serde(rename = "quickPlayTime", default)]
+	pub time: Option<String>
+}
@@ -1,21 +1,33 @@
 mod login;
 mod manager;
 mod main;
+mod console;
+mod settings;
+mod new_instance;
+mod import;
 
 use std::time::SystemTime;
-use iced::{Application, Command, executor, font, Length, Renderer};
+use iced::{Application, Command, executor, font, Length, Renderer, Subscription};
 use iced::widget::{button, container, text};
 use iced_aw::{Card, CardStyles, modal};
 use crate::backend::{refresh_mc, refresh_ms, save_account_to_file};
 use crate::model::Account;
+use crate::ui::console::{ConsoleMessage, ConsoleUi};
+use crate::ui::import::{ImportMessage, ImportUi};
 use crate::ui::login::{LoginMessage, LoginUi};
 use crate::ui::main::{MainMessage, MainUi};
+use crate::ui::new_instance::{NewInstanceMessage, NewInstanceUi};
+use crate::ui::settings::{SettingsMessage, SettingsUi};
 
 #[derive(Debug, Clone)]
 pub enum Message {
 	FontLoaded(Result<(), font::Error>),
 	Login(LoginMessage),
 	Main(MainMessage),
+	Console(ConsoleMessage),
+	Settings(SettingsMessage),
+	NewInstance(NewInstanceMessage),
+	Import(ImportMessage),
 	Logout,
 	AccountRefreshed(Result<Account, String>),
 	ModalClose
@@ -26,20 +38,38 @@ enum View {
 	Loading,
 	Login,
 	Main,
+	Console,
+	Settings,
+	NewInstance,
+	Import
 }
 
 struct Modal<'a, Message> {
+	title: &'static str,
 	body: Element<'a, Message>,
-	foot: Option<Element<'a, Message>>
+	foot: Option<Element<'a, Message>>,
+	max_width: f32,
+	max_height: f32
 }
 
 impl<'a, Message> Modal<'a, Message> {
 	fn new(body: Element<'a, Message>) -> Self {
-		Self { body, foot: None }
+		Self { title: "Error", body, foot: None, max_width: 160.0, max_height: 320.0 }
 	}
 
 	fn with_foot(body: Element<'a, Message>, foot: Element<'a, Message>) -> Self {
-		Self { body, foot: Some(foot) }
+		Self { foot: Some(foot), ..Self::new(body) }
+	}
+
+	fn title(mut self, title: &'static str) -> Self {
+		self.title = title;
+		self
+	}
+
+	fn size(mut self, max_width: f32, max_height: f32) -> Self {
+		self.max_width = max_width;
+		self.max_height = max_height;
+		self
 	}
 }
 
@@ -50,7 +80,11 @@ pub struct Ui<'a> {
 	client: reqwest::Client,
 	modal: Option<Box<dyn Fn(&Ui<'a>) -> Modal<'a, Message>>>,
 	main_modal: Option<Box<dyn Fn() -> Modal<'a, MainMessage>>>,
-	main_ui: MainUi
+	main_ui: MainUi,
+	console_ui: ConsoleUi,
+	settings_ui: SettingsUi,
+	new_instance_ui: NewInstanceUi,
+	import_ui: ImportUi
 }
 
 pub type Element<'a, Message> = iced::Element<'a, Message, Renderer>;
@@ -100,12 +134,16 @@ impl<'a> Application for Ui<'a> {
 			modal: None,
 			main_modal: None,
 			main_ui,
+			console_ui: ConsoleUi::new(),
+			settings_ui: SettingsUi::new(),
+			new_instance_ui: NewInstanceUi::new(),
+			import_ui: ImportUi::new()
 		};
 		let (refresh, refresh_cmd) = s.refresh_account();
 		if refresh {
 			s.view = View::Loading;
 		} else {
-			s.main_ui.mc_manager.inner.blocking_lock().account = s.account.clone();
+			s.main_ui.account = s.account.clone();
 		}
 		(
 			s,
@@ -128,7 +166,7 @@ impl<'a> Application for Ui<'a> {
 					Ok(acc) => {
 						self.account = Some(acc);
 						self.view = View::Main;
-						self.main_ui.mc_manager.inner.blocking_lock().account = self.account.clone();
+						self.main_ui.account = self.account.clone();
 						if let Err(err) = save_account_to_file(self.account.as_ref().unwrap()) {
 							self.modal = Some(Box::new(move |_| Modal::new(
 								text(format!("Failed to save account info to a file: {}", err)).into()
@@ -147,13 +185,59 @@ impl<'a> Application for Ui<'a> {
 			Message::Login(message) => {
 				self.login_ui.update(message).map(Message::Login)
 			},
+			Message::Main(MainMessage::OpenConsole(game)) => {
+				self.view = View::Console;
+				self.main_modal = None;
+				self.console_ui.set_instance(self.main_ui.selected_instance_id());
+				if let Some(game) = game {
+					self.console_ui.select(game);
+				}
+				Command::none()
+			}
+			Message::Main(MainMessage::OpenSettings(instance)) => {
+				self.view = View::Settings;
+				self.settings_ui.load(instance).map(Message::Settings)
+			}
+			Message::Main(MainMessage::OpenNewInstance) => {
+				self.view = View::NewInstance;
+				self.new_instance_ui.load(self.main_ui.mc_manager.clone()).map(Message::NewInstance)
+			}
+			Message::Main(MainMessage::OpenImport) => {
+				self.view = View::Import;
+				self.import_ui.load(self.main_ui.mc_manager.clone()).map(Message::Import)
+			}
 			Message::Main(message) => self.main_ui.update(&mut self.main_modal, message).map(Message::Main),
+			Message::Console(ConsoleMessage::Back) => {
+				self.view = View::Main;
+				Command::none()
+			}
+			Message::Console(message) => self.console_ui.update(message).map(Message::Console),
+			Message::Settings(SettingsMessage::Back) => {
+				self.view = View::Main;
+				// the name or version may have changed
+				self.main_ui.load_instances().map(Message::Main)
+			}
+			Message::Settings(message) => self.settings_ui.update(message).map(Message::Settings),
+			Message::NewInstance(NewInstanceMessage::Created(Ok(instance))) => {
+				self.view = View::Main;
+				self.main_ui.update(&mut self.main_modal, MainMessage::InstanceCreated(Ok(instance))).map(Message::Main)
+			}
+			Message::NewInstance(NewInstanceMessage::Cancel) => {
+				self.view = View::Main;
+				Command::none()
+			}
+			Message::NewInstance(message) => self.new_instance_ui.update(message).map(Message::NewInstance),
+			Message::Import(ImportMessage::Back) => {
+				self.view = View::Main;
+				self.main_ui.load_instances().map(Message::Main)
+			}
+			Message::Import(message) => self.import_ui.update(message).map(Message::Import),
 			Message::AccountRefreshed(res) => {
 				match res {
 					Ok(acc) => {
 						self.account = Some(acc);
 						self.view = View::Main;
-						self.main_ui.mc_manager.inner.blocking_lock().account = self.account.clone();
+						self.main_ui.account = self.account.clone();
 						if let Err(err) = save_account_to_file(self.account.as_ref().unwrap()) {
 							self.modal = Some(Box::new(move |_| Modal::new(
 								text(format!("Failed to save refreshed account info to a file: {}", err)).into()
@@ -171,6 +255,7 @@ impl<'a> Application for Ui<'a> {
 			}
 			Message::ModalClose => {
 				self.modal = None;
+				self.main_modal = None;
 				Command::none()
 			}
 			Message::FontLoaded(Ok(())) => Command::none(),
@@ -202,13 +287,25 @@ impl<'a> Application for Ui<'a> {
 			View::Main => {
 				self.main_ui.view().map(Message::Main)
 			}
+			View::Console => {
+				self.console_ui.view().map(Message::Console)
+			}
+			View::Settings => {
+				self.settings_ui.view().map(Message::Settings)
+			}
+			View::NewInstance => {
+				self.new_instance_ui.view().map(Message::NewInstance)
+			}
+			View::Import => {
+				self.import_ui.view().map(Message::Import)
+			}
 		};
 		let overlay = if let Some(f) = &self.modal {
 			let modal = f(self);
 			Some(
 				{
 					let mut card = Card::new(
-						text("Error"),
+						text(modal.title),
 						container(modal.body)
 							.width(Length::Fill)
 							.height(Length::Shrink)
@@ -216,8 +313,8 @@ impl<'a> Application for Ui<'a> {
 							.center_y())
 						.width(Length::Fill)
 						.height(Length::Fill)
-						.max_width(160.0)
-						.max_height(320.0)
+						.max_width(modal.max_width)
+						.max_height(modal.max_height)
 						.on_close(Message::ModalClose)
 						.style(CardStyles::Primary);
 					if let Some(foot) = modal.foot {
@@ -231,7 +328,7 @@ impl<'a> Application for Ui<'a> {
 			Some(
 				{
 					let mut card = Card::new(
-						text("Error"),
+						text(modal.title),
 						container(modal.body.map(Message::Main))
 							.width(Length::Fill)
 							.height(Length::Shrink)
@@ -239,8 +336,8 @@ impl<'a> Application for Ui<'a> {
 							.center_y())
 						.width(Length::Fill)
 						.height(Length::Fill)
-						.max_width(160.0)
-						.max_height(320.0)
+						.max_width(modal.max_width)
+						.max_height(modal.max_height)
 						.on_close(Message::ModalClose)
 						.style(CardStyles::Primary);
 					if let Some(foot) = modal.foot {
@@ -256,6 +353,13 @@ impl<'a> Application for Ui<'a> {
 			.into()
 	}
 
+	fn subscription(&self) -> Subscription<Self::Message> {
+		Subscription::batch([
+			self.console_ui.subscription(&self.main_ui.mc_manager).map(Message::Console),
+			self.main_ui.subscription().map(Message::Main)
+		])
+	}
+
 	fn theme(&self) -> Self::Theme {
 		Theme::Dark
 	}
@@ -0,0 +1,373 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use iced::{Alignment, Color, Command, Length, Subscription, subscription};
+use iced::futures::SinkExt;
+use iced::widget::{button, checkbox, Column, container, horizontal_space, pick_list, Row, scrollable, text, text_input};
+use iced::widget::scrollable::RelativeOffset;
+use tokio::sync::broadcast;
+use crate::backend::{game_logs_dir, GameEvent, GameState, LogLevel, LogLine, MAX_LINES, parse_log, previous_game_log};
+use crate::ui::Element;
+use crate::ui::manager::UiManagerWrapper;
+
+/// Amount of (filtered) lines that are turned into widgets, everything older is only kept in memory.
+const MAX_VISIBLE_LINES: usize = 1000;
+
+#[derive(Debug, Clone)]
+pub enum ConsoleMessage {
+	Event(GameEvent),
+	GameSelected(GameOption),
+	FilterChanged(String),
+	PauseToggled(bool),
+	AutoScrollToggled(bool),
+	Copy,
+	ShowPrevious,
+	PreviousLoaded(Result<Option<(PathBuf, String)>, String>),
+	ShowCurrent,
+	Back
+}
+
+type Message = ConsoleMessage;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameOption {
+	id: usize,
+	version: String
+}
+
+impl Display for GameOption {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		write!(f, "#{} {}", self.id + 1, self.version)
+	}
+}
+
+struct ConsoleGame {
+	id: usize,
+	instance_id: String,
+	version: String,
+	log_file: PathBuf,
+	state: GameState,
+	lines: Vec<LogLine>
+}
+
+enum Source {
+	Current,
+	Previous(PathBuf, Vec<LogLine>)
+}
+
+pub struct ConsoleUi {
+	games: Vec<ConsoleGame>,
+	/// Index into `games` of the game whose output is shown.
+	selected: Option<usize>,
+	source: Source,
+	filter: String,
+	/// Amount of lines shown while the output is paused.
+	paused: Option<usize>,
+	auto_scroll: bool,
+	error: Option<String>,
+	/// Formatted tail of the filtered lines that are shown, so redraws don't filter everything.
+	visible: Vec<(String, Option<Color>)>,
+	/// Instance whose previous launch is shown when no game is selected.
+	instance_id: Option<String>,
+	scroll_id: scrollable::Id
+}
+
+impl ConsoleUi {
+	pub fn new() -> Self {
+		Self {
+			games: Vec::new(),
+			selected: None,
+			source: Source::Current,
+			filter: String::new(),
+			paused: None,
+			auto_scroll: true,
+			error: None,
+			visible: Vec::new(),
+			instance_id: None,
+			scroll_id: scrollable::Id::unique()
+		}
+	}
+
+	pub fn subscription(&self, mc_manager: &UiManagerWrapper) -> Subscription<Message> {
+		let mc_manager = mc_manager.clone();
+		subscription::channel("game-events", 100, move |mut output| async move {
+			let mut receiver = mc_manager.subscribe_game_events();
+			loop {
+				match receiver.recv().await {
+					Ok(event) => {
+						output.send(Message::Event(event)).await.ok();
+					}
+					Err(broadcast::error::RecvError::Lagged(count)) => {
+						eprintln!("warning: console skipped {} game events", count);
+					}
+					Err(broadcast::error::RecvError::Closed) => {
+						receiver = mc_manager.subscribe_game_events();
+					}
+				}
+			}
+		})
+	}
+
+	/// Sets the instance whose previous launch can be shown without a game of it running.
+	pub fn set_instance(&mut self, instance_id: Option<String>) {
+		self.instance_id = instance_id;
+	}
+
+	/// Shows the output of the game with the given id.
+	pub fn select(&mut self, id: usize) {
+		if let Some(index) = self.games.iter().position(|game| game.id == id) {
+			self.selected = Some(index);
+			self.source = Source::Current;
+			self.paused = None;
+			self.refresh_visible();
+		}
+	}
+
+	pub fn update(&mut self, message: Message) -> Command<Message> {
+		match message {
+			Message::Event(GameEvent::Started { id, instance_id, version, log_file }) => {
+				self.games.push(ConsoleGame {
+					id,
+					instance_id,
+					version,
+					log_file,
+					state: GameState::Starting,
+					lines: Vec::new()
+				});
+				// follow the newest game unless the user is looking at something else
+				if self.paused.is_none() && matches!(self.source, Source::Current) {
+					self.selected = Some(self.games.len() - 1);
+					self.refresh_visible();
+				}
+				Command::none()
+			}
+			Message::Event(GameEvent::Line(id, line)) => {
+				let index = match self.games.iter().position(|game| game.id == id) {
+					Some(index) => index,
+					None => return Command::none()
+				};
+				let shown = self.selected == Some(index);
+				let game = &mut self.games[index];
+				if game.lines.len() >= MAX_LINES {
+					game.lines.drain(..MAX_LINES / 10);
+					// the paused view keeps showing the same lines, they just start earlier now
+					if let Some(paused) = self.paused.as_mut().filter(|_| shown) {
+						*paused = paused.saturating_sub(MAX_LINES / 10);
+					}
+				}
+				if shown && self.paused.is_none() && matches!(self.source, Source::Current) {
+					self.push_visible(&line);
+				}
+				self.games[index].lines.push(line);
+				self.snap_to_end()
+			}
+			Message::Event(GameEvent::State(id, state)) => {
+				if let Some(game) = self.games.iter_mut().find(|game| game.id == id) {
+					game.state = state;
+				}
+				Command::none()
+			}
+			Message::GameSelected(option) => {
+				self.select(option.id);
+				self.snap_to_end()
+			}
+			Message::FilterChanged(filter) => {
+				self.filter = filter;
+				self.refresh_visible();
+				Command::none()
+			}
+			Message::PauseToggled(paused) => {
+				self.paused = if paused {
+					Some(self.current_game().map_or(0, |game| game.lines.len()))
+				} else {
+					None
+				};
+				self.refresh_visible();
+				self.snap_to_end()
+			}
+			Message::AutoScrollToggled(auto_scroll) => {
+				self.auto_scroll = auto_scroll;
+				self.snap_to_end()
+			}
+			Message::Copy => {
+				let text = self.filtered_lines()
+					.map(format_line)
+					.collect::<Vec<_>>()
+					.join("\n");
+				iced::clipboard::write(text)
+			}
+			Message::ShowPrevious => {
+				let exclude = self.current_game().map(|game| game.log_file.clone());
+				let instance_id = match self.current_game().map(|game| game.instance_id.clone()).or_else(|| self.instance_id.clone()) {
+					Some(instance_id) => instance_id,
+					None => {
+						self.error = Some("Select an instance to show its previous launch".to_string());
+						return Command::none();
+					}
+				};
+				Command::perform(async move {
+					previous_game_log(&game_logs_dir(&instance_id), exclude.as_deref()).await
+						.map_err(|err| err.to_string())
+				}, Message::PreviousLoaded)
+			}
+			Message::PreviousLoaded(res) => {
+				match res {
+					Ok(Some((path, contents))) => {
+						self.error = None;
+						self.source = Source::Previous(path, parse_log(&contents));
+						self.refresh_visible();
+					}
+					Ok(None) => self.error = Some("There is no previous launch log".to_string()),
+					Err(err) => self.error = Some(format!("Failed to read the previous launch log: {}", err))
+				}
+				Command::none()
+			}
+			Message::ShowCurrent => {
+				self.source = Source::Current;
+				self.error = None;
+				self.refresh_visible();
+				self.snap_to_end()
+			}
+			Message::Back => Command::none()
+		}
+	}
+
+	fn snap_to_end(&self) -> Command<Message> {
+		if self.auto_scroll && self.paused.is_none() && matches!(self.source, Source::Current) {
+			scrollable::snap_to(self.scroll_id.clone(), RelativeOffset::END)
+		} else {
+			Command::none()
+		}
+	}
+
+	fn current_game(&self) -> Option<&ConsoleGame> {
+		self.selected.map(|index| &self.games[index])
+	}
+
+	fn source_lines(&self) -> &[LogLine] {
+		match &self.source {
+			Source::Current => match self.current_game() {
+				Some(game) => &game.lines[..self.paused.unwrap_or(usize::MAX).min(game.lines.len())],
+				None => &[]
+			},
+			Source::Previous(_, lines) => lines
+		}
+	}
+
+	fn filtered_lines(&self) -> impl Iterator<Item = &LogLine> {
+		let filter = self.filter.to_lowercase();
+		self.source_lines().iter().filter(move |line| {
+			filter.is_empty() || format_line(line).to_lowercase().contains(&filter)
+		})
+	}
+
+	/// Rebuilds the shown lines after the source, filter or pause state changed.
+	fn refresh_visible(&mut self) {
+		let lines: Vec<_> = self.filtered_lines().collect();
+		let skip = lines.len().saturating_sub(MAX_VISIBLE_LINES);
+		let visible = lines.into_iter()
+			.skip(skip)
+			.map(|line| (format_line(line), line.level.and_then(level_color)))
+			.collect();
+		self.visible = visible;
+	}
+
+	/// Adds a new line of the shown game if it matches the filter.
+	fn push_visible(&mut self, line: &LogLine) {
+		let formatted = format_line(line);
+		if !self.filter.is_empty() && !formatted.to_lowercase().contains(&self.filter.to_lowercase()) {
+			return;
+		}
+		// trimmed in batches rather than on every line
+		if self.visible.len() >= MAX_VISIBLE_LINES * 2 {
+			self.visible.drain(..MAX_VISIBLE_LINES);
+		}
+		self.visible.push((formatted, line.level.and_then(level_color)));
+	}
+
+	pub fn view(&self) -> Element<'_, Message> {
+		let status = match (&self.source, self.current_game()) {
+			(Source::Previous(path, _), _) => format!("Previous launch: {}", path.display()),
+			(Source::Current, None) => "No game has been launched yet".to_string(),
+			(Source::Current, Some(game)) => match game.state {
+				GameState::Starting => "starting".to_string(),
+				GameState::Running => "running".to_string(),
+				GameState::Exited(Some(code)) => format!("exited with code {}", code),
+				GameState::Exited(None) => "terminated".to_string()
+			}
+		};
+
+		let options: Vec<GameOption> = self.games.iter().map(|game| GameOption {
+			id: game.id,
+			version: game.version.clone()
+		}).collect();
+		let selected = self.current_game().map(|game| GameOption {
+			id: game.id,
+			version: game.version.clone()
+		});
+
+		let source_button = match self.source {
+			Source::Current => button("Previous launch").on_press(Message::ShowPrevious),
+			Source::Previous(..) => button("Current launch").on_press(Message::ShowCurrent)
+		};
+
+		let header = Row::new()
+			.push(button("Back").on_press(Message::Back))
+			.push(pick_list(options, selected, Message::GameSelected))
+			.push(text(status))
+			.push(horizontal_space(Length::Fill))
+			.push(source_button)
+			.push(button("Copy").on_press(Message::Copy))
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let controls = Row::new()
+			.push(text_input("Filter", &self.filter).on_input(Message::FilterChanged))
+			.push(checkbox("Pause", self.paused.is_some(), Message::PauseToggled))
+			.push(checkbox("Auto-scroll", self.auto_scroll, Message::AutoScrollToggled))
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let skip = self.visible.len().saturating_sub(MAX_VISIBLE_LINES);
+		let log = Column::with_children(self.visible[skip..].iter().map(|(line, color)| {
+			let mut line_text = text(line).size(14);
+			if let Some(color) = color {
+				line_text = line_text.style(*color);
+			}
+			line_text.into()
+		}).collect::<Vec<Element<'_, Message>>>());
+
+		let mut content = Column::new()
+			.push(header)
+			.push(controls)
+			.spacing(10)
+			.padding(10);
+		if let Some(error) = &self.error {
+			content = content.push(text(error).style(Color::from_rgb8(0xE0, 0x6C, 0x75)));
+		}
+		content = content.push(scrollable(log)
+			.id(self.scroll_id.clone())
+			.width(Length::Fill)
+			.height(Length::Fill));
+
+		container(content)
+			.width(Length::Fill)
+			.height(Length::Fill)
+			.into()
+	}
+}
+
+fn format_line(line: &LogLine) -> String {
+	match (&line.thread, line.level) {
+		(Some(thread), Some(level)) => format!("[{}/{}] {}", thread, level.as_str(), line.message),
+		_ => line.message.clone()
+	}
+}
+
+fn level_color(level: LogLevel) -> Option<Color> {
+	match level {
+		LogLevel::Trace | LogLevel::Debug => Some(Color::from_rgb8(0x8A, 0x8F, 0x98)),
+		LogLevel::Info => None,
+		LogLevel::Warn => Some(Color::from_rgb8(0xE5, 0xC0, 0x7B)),
+		LogLevel::Error | LogLevel::Fatal => Some(Color::from_rgb8(0xE0, 0x6C, 0x75))
+	}
+}
@@ -0,0 +1,225 @@
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use iced::{Alignment, Color, Command, Length};
+use iced::widget::{button, checkbox, Column, container, horizontal_space, pick_list, Row, scrollable, text, text_input};
+use crate::backend::{atlauncher_dir, gdlauncher_dir, ImportReport, McResult, official_launcher_dir, prism_launcher_dir};
+use crate::ui::Element;
+use crate::ui::manager::UiManagerWrapper;
+
+#[derive(Debug, Clone)]
+pub enum ImportMessage {
+	SourceSelected(ImportSource),
+	DirChanged(String),
+	ReuseFilesToggled(bool),
+	MoveFilesToggled(bool),
+	Import,
+	Imported(McResult<ImportReport>),
+	Back
+}
+
+type Message = ImportMessage;
+
+/// Launchers instances can be imported from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportSource {
+	Official,
+	/// MultiMC and its forks, which share the instance format.
+	Prism,
+	AtLauncher,
+	GdLauncher,
+	/// A zip written by exporting an instance.
+	Archive
+}
+
+impl ImportSource {
+	const ALL: [ImportSource; 5] = [
+		ImportSource::Official,
+		ImportSource::Prism,
+		ImportSource::AtLauncher,
+		ImportSource::GdLauncher,
+		ImportSource::Archive
+	];
+
+	/// Where the launcher keeps its data by default.
+	fn default_dir(&self) -> Option<PathBuf> {
+		match self {
+			ImportSource::Official => official_launcher_dir(),
+			ImportSource::Prism => prism_launcher_dir(),
+			ImportSource::AtLauncher => atlauncher_dir(),
+			ImportSource::GdLauncher => gdlauncher_dir(),
+			ImportSource::Archive => None
+		}
+	}
+}
+
+impl Display for ImportSource {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ImportSource::Official => write!(f, "Minecraft Launcher"),
+			ImportSource::Prism => write!(f, "Prism Launcher / MultiMC"),
+			ImportSource::AtLauncher => write!(f, "ATLauncher"),
+			ImportSource::GdLauncher => write!(f, "GDLauncher"),
+			ImportSource::Archive => write!(f, "Instance archive")
+		}
+	}
+}
+
+pub struct ImportUi {
+	mc_manager: Option<UiManagerWrapper>,
+	source: ImportSource,
+	dir: String,
+	/// Link the libraries and assets of the other launcher instead of downloading them again.
+	reuse_files: bool,
+	/// Move game directories out of the other launcher instead of copying them.
+	move_files: bool,
+	importing: bool,
+	result: Option<Result<ImportReport, String>>
+}
+
+impl ImportUi {
+	pub fn new() -> Self {
+		Self {
+			mc_manager: None,
+			source: ImportSource::Official,
+			dir: String::new(),
+			reuse_files: true,
+			move_files: false,
+			importing: false,
+			result: None
+		}
+	}
+
+	pub fn load(&mut self, mc_manager: UiManagerWrapper) -> Command<Message> {
+		*self = Self {
+			mc_manager: Some(mc_manager),
+			..Self::new()
+		};
+		self.update(Message::SourceSelected(ImportSource::Official))
+	}
+
+	pub fn update(&mut self, message: Message) -> Command<Message> {
+		match message {
+			Message::SourceSelected(source) => {
+				self.source = source;
+				self.dir = source.default_dir()
+					.map(|dir| dir.display().to_string())
+					.unwrap_or_default();
+				self.result = None;
+				Command::none()
+			}
+			Message::DirChanged(dir) => {
+				self.dir = dir;
+				Command::none()
+			}
+			Message::ReuseFilesToggled(value) => {
+				self.reuse_files = value;
+				Command::none()
+			}
+			Message::MoveFilesToggled(value) => {
+				self.move_files = value;
+				Command::none()
+			}
+			Message::Import => {
+				let mc_manager = match &self.mc_manager {
+					Some(mc_manager) => mc_manager.clone(),
+					None => return Command::none()
+				};
+				let dir = PathBuf::from(self.dir.trim());
+				self.importing = true;
+				self.result = None;
+				match self.source {
+					ImportSource::Official => Command::perform(mc_manager.import_official(dir, self.reuse_files), Message::Imported),
+					ImportSource::Prism => Command::perform(mc_manager.import_prism(dir, self.move_files), Message::Imported),
+					ImportSource::AtLauncher => Command::perform(mc_manager.import_atlauncher(dir, self.move_files), Message::Imported),
+					ImportSource::GdLauncher => Command::perform(mc_manager.import_gdlauncher(dir, self.move_files), Message::Imported),
+					ImportSource::Archive => Command::perform(mc_manager.import_archive(dir), Message::Imported)
+				}
+			}
+			Message::Imported(res) => {
+				self.importing = false;
+				self.result = Some(res.map_err(|err| err.to_string()));
+				Command::none()
+			}
+			Message::Back => Command::none()
+		}
+	}
+
+	pub fn view(&self) -> Element<'_, Message> {
+		let header = Row::new()
+			.push(button("Back").on_press(Message::Back))
+			.push(text("Import instances").size(20))
+			.push(horizontal_space(Length::Fill))
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let mut import_button = button(if self.importing { "Importing..." } else { "Import" });
+		if !self.importing && !self.dir.trim().is_empty() {
+			import_button = import_button.on_press(Message::Import);
+		}
+
+		let mut content = Column::new()
+			.push(header)
+			.push(Row::new()
+				.push(text("Launcher").width(Length::Fixed(200.0)))
+				.push(pick_list(&ImportSource::ALL[..], Some(self.source), Message::SourceSelected))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(Row::new()
+				.push(text(if self.source == ImportSource::Archive { "Archive" } else { "Directory" }).width(Length::Fixed(200.0)))
+				.push(text_input(
+					if self.source == ImportSource::Archive { "Path to the .zip file" } else { "Path to the launcher directory" },
+					&self.dir
+				).on_input(Message::DirChanged))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(self.options_view())
+			.push(import_button)
+			.spacing(10)
+			.padding(10);
+
+		match &self.result {
+			Some(Ok(report)) => {
+				let mut lines = Column::new()
+					.push(text(format!("Imported {} instance(s)", report.instances.len())))
+					.spacing(5);
+				for instance in &report.instances {
+					let mods = if instance.mods.is_empty() {
+						String::new()
+					} else {
+						format!(", {} mod(s)", instance.mods.len())
+					};
+					lines = lines.push(text(format!("  {} ({} {}{})", instance.name, instance.loader, instance.version, mods)).size(12));
+				}
+				for warning in &report.warnings {
+					lines = lines.push(text(warning).size(12).style(Color::from_rgb8(0xE5, 0xC0, 0x7B)));
+				}
+				content = content.push(scrollable(lines).height(Length::Fill));
+			}
+			Some(Err(err)) => {
+				content = content.push(text(format!("Failed to import: {}", err)).style(Color::from_rgb8(0xE0, 0x6C, 0x75)));
+			}
+			None => {}
+		}
+
+		container(content)
+			.width(Length::Fill)
+			.height(Length::Fill)
+			.into()
+	}
+
+	fn options_view(&self) -> Element<'_, Message> {
+		match self.source {
+			ImportSource::Official => checkbox(
+				"Reuse downloaded libraries and assets",
+				self.reuse_files,
+				Message::ReuseFilesToggled
+			).into(),
+			ImportSource::Prism | ImportSource::AtLauncher | ImportSource::GdLauncher => checkbox(
+				"Move the game directories instead of copying them",
+				self.move_files,
+				Message::MoveFilesToggled
+			).into(),
+			ImportSource::Archive => Column::new().into()
+		}
+	}
+}
@@ -1,37 +1,239 @@
-use iced::{Alignment, Command, Length, Renderer};
-use iced::widget::{button, Column, container, pick_list, PickList, text};
-use crate::backend::{McDownloader, McResult};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
+use iced::{Alignment, Command, Length, Renderer, Subscription};
+use iced::widget::{Button, button, checkbox, Column, container, image, pick_list, Row, scrollable, text, text_input};
+use crate::backend::{ACCESS_TOKEN_VAR, ARCHIVE_FOLDERS, command_line, CrashReport, delete_instance, duplicate_instance, export_history, export_instance, format_unix_millis, GameProcess, GameState, HistoryFormat, instance_dir, instance_game_dir, instance_icon, InstanceInfo, LaunchCommand, LaunchOptions, list_instances, load_history, load_instance_info, McDownloader, McResult, migrate_legacy_instance, open_folder, QuickPlay, read_quick_play_log, save_instance, unix_millis, wait_for_crash};
+use crate::model::{Account, all_versions, Instance, LaunchRecord, QuickPlayEntry, total_playtime};
 use crate::ui::manager::UiManagerWrapper;
 use crate::ui::{Element, Modal};
 
 #[derive(Debug, Clone)]
 pub enum MainMessage {
 	LoadVersions,
-	VersionsLoaded(McResult<()>),
-	VersionSelected(String),
+	VersionsLoaded(McResult<all_versions::Versions>),
+	LoadInstances,
+	InstancesLoaded(McResult<Vec<Instance>>),
+	InstanceInfoLoaded(Vec<(String, InstanceInfo)>),
+	FilterChanged(String),
+	SortChanged(InstanceSort),
+	ToggleGroup(String),
+	ToggleFavourite(String),
+	InstanceSaved(McResult<()>),
+	SelectInstance(String),
+	LaunchInstance(String),
+	OpenFolder(String),
+	FolderOpened(McResult<()>),
+	DuplicateInstance(String),
+	ConfirmDuplicateInstance(String, bool),
+	OpenNewInstance,
+	OpenImport,
+	InstanceCreated(McResult<Instance>),
+	DeleteInstance(String),
+	ConfirmDeleteInstance(String),
+	InstanceDeleted(McResult<()>),
+	CustomLogConfigToggled(bool),
 	Play,
-	PlayFinished(McResult<()>)
+	ServerChanged(String),
+	WorldChanged(String),
+	QuickPlay(QuickPlay),
+	QuickPlayLogLoaded(McResult<Vec<QuickPlayEntry>>),
+	HistoryLoaded(McResult<Vec<LaunchRecord>>),
+	ExportHistory(HistoryFormat),
+	HistoryExported(McResult<PathBuf>),
+	TokenPlaceholderToggled(bool),
+	ExportScript,
+	ScriptExported(McResult<PathBuf>),
+	ExportFolderToggled(usize, bool),
+	ExportInstance,
+	InstanceExported(McResult<PathBuf>),
+	ShowLaunchCommand,
+	LaunchCommandLoaded(McResult<LaunchCommand>),
+	CopyText(String),
+	Launched(McResult<Arc<GameProcess>>),
+	GameExited(usize, McResult<Option<CrashReport>>),
+	StopGame(usize),
+	GameStopped,
+	DismissGame(usize),
+	Tick,
+	OpenConsole(Option<usize>),
+	OpenSettings(String)
 }
 
 type Message = MainMessage;
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InstanceSort {
+	Name,
+	LastPlayed,
+	Version
+}
+
+impl InstanceSort {
+	const ALL: [InstanceSort; 3] = [InstanceSort::Name, InstanceSort::LastPlayed, InstanceSort::Version];
+}
+
+impl Display for InstanceSort {
+	fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+		match self {
+			InstanceSort::Name => write!(f, "Name"),
+			InstanceSort::LastPlayed => write!(f, "Last played"),
+			InstanceSort::Version => write!(f, "Version")
+		}
+	}
+}
+
+/// How long a game gets to exit on its own before it's killed.
+const STOP_TIMEOUT: Duration = Duration::from_secs(10);
+
 pub struct MainUi {
 	pub mc_manager: UiManagerWrapper,
+	pub account: Option<Account>,
+	running: Vec<Arc<GameProcess>>,
+	launching: usize,
+	instances: Vec<Instance>,
+	instance_info: HashMap<String, InstanceInfo>,
+	filter: String,
+	sort: InstanceSort,
+	/// Groups whose instances are hidden in the list.
+	collapsed_groups: HashSet<String>,
+	/// Id of the instance whose details are shown.
+	selected_instance: Option<String>,
+	/// Ids of all versions, newest first.
 	version_options: Vec<String>,
-	selected_version: Option<String>
+	launch_options: LaunchOptions,
+	server: String,
+	world: String,
+	recent_quick_plays: Vec<QuickPlayEntry>,
+	history: Vec<LaunchRecord>,
+	/// Leave the access token out of exported launch scripts.
+	token_placeholder: bool,
+	/// Which of [`ARCHIVE_FOLDERS`] go into exported instances.
+	export_folders: [bool; 5],
+	export_status: Option<String>
 }
 
 impl MainUi {
 	pub fn new(client: reqwest::Client) -> (Self, Command<Message>) {
 		let s = Self {
 			mc_manager: UiManagerWrapper::new(McDownloader::new(client)),
+			account: None,
+			running: Vec::new(),
+			launching: 0,
+			instances: Vec::new(),
+			instance_info: HashMap::new(),
+			filter: String::new(),
+			sort: InstanceSort::Name,
+			collapsed_groups: HashSet::new(),
+			selected_instance: None,
 			version_options: Vec::new(),
-			selected_version: None
+			launch_options: LaunchOptions::default(),
+			server: String::new(),
+			world: String::new(),
+			recent_quick_plays: Vec::new(),
+			history: Vec::new(),
+			token_placeholder: true,
+			// worlds can be huge and are often not meant to be shared
+			export_folders: [true, true, true, true, false],
+			export_status: None
 		};
 
 		let versions_load_cmd = Command::perform(s.mc_manager.clone().load_versions(), Message::VersionsLoaded);
+		let instances_load_cmd = Command::perform(async {
+			if let Err(err) = migrate_legacy_instance().await {
+				eprintln!("error: failed to migrate the old instance: {}", err);
+			}
+			list_instances().await
+		}, Message::InstancesLoaded);
+
+		(s, Command::batch([versions_load_cmd, instances_load_cmd]))
+	}
 
-		(s, versions_load_cmd)
+	pub fn load_instances(&self) -> Command<Message> {
+		Command::perform(list_instances(), Message::InstancesLoaded)
+	}
+
+	pub fn selected_instance_id(&self) -> Option<String> {
+		self.selected_instance.clone()
+	}
+
+	fn selected(&self) -> Option<&Instance> {
+		let id = self.selected_instance.as_ref()?;
+		self.instances.iter().find(|instance| &instance.id == id)
+	}
+
+	/// Loads the quick play log and launch history of the selected instance.
+	fn load_instance_data(&self) -> Command<Message> {
+		let instance = match self.selected() {
+			Some(instance) => instance,
+			None => return Command::none()
+		};
+		let game_dir = instance_game_dir(instance);
+		let instance_dir = instance_dir(&instance.id);
+		Command::batch([
+			Command::perform(async move { read_quick_play_log(&game_dir).await }, Message::QuickPlayLogLoaded),
+			Command::perform(async move { load_history(&instance_dir).await }, Message::HistoryLoaded)
+		])
+	}
+
+	/// Refreshes the install state and last launch of every instance.
+	fn load_instance_info(&self) -> Command<Message> {
+		let instances = self.instances.clone();
+		Command::perform(async move {
+			let mut info = Vec::with_capacity(instances.len());
+			for instance in instances {
+				match load_instance_info(&instance).await {
+					Ok(instance_info) => info.push((instance.id, instance_info)),
+					Err(err) => eprintln!("error: failed to load the state of {}: {}", instance.id, err)
+				}
+			}
+			info
+		}, Message::InstanceInfoLoaded)
+	}
+
+	fn is_running(&self, instance_id: &str) -> bool {
+		self.running.iter().any(|game| game.instance_id == instance_id && !matches!(game.state(), GameState::Exited(_)))
+	}
+
+	/// Instances matching the filter in the selected order, favourites first.
+	fn visible_instances(&self) -> Vec<&Instance> {
+		let filter = self.filter.to_lowercase();
+		let mut instances: Vec<&Instance> = self.instances.iter()
+			.filter(|instance| filter.is_empty()
+				|| instance.name.to_lowercase().contains(&filter)
+				|| instance.version.to_lowercase().contains(&filter)
+				|| instance.loader.id().contains(&filter)
+				|| instance.group.as_ref().is_some_and(|group| group.to_lowercase().contains(&filter))
+				|| instance.notes.to_lowercase().contains(&filter))
+			.collect();
+		match self.sort {
+			InstanceSort::Name => {}
+			InstanceSort::LastPlayed => instances.sort_by_key(|instance| {
+				std::cmp::Reverse(self.instance_info.get(&instance.id).and_then(|info| info.last_played))
+			}),
+			// the manifest lists the newest versions first
+			InstanceSort::Version => instances.sort_by_key(|instance| {
+				self.version_options.iter().position(|version| *version == instance.version).unwrap_or(usize::MAX)
+			})
+		}
+		// stable, so the selected order is kept among favourites and the rest
+		instances.sort_by_key(|instance| !instance.favourite);
+		instances
+	}
+
+	fn launch(&mut self, quick_play: Option<QuickPlay>) -> Command<Message> {
+		let (account, instance) = match (&self.account, &self.selected_instance) {
+			(Some(account), Some(instance)) => (account.clone(), instance.clone()),
+			_ => return Command::none()
+		};
+		let options = LaunchOptions {
+			quick_play,
+			..self.launch_options.clone()
+		};
+		self.launching += 1;
+		Command::perform(self.mc_manager.clone().launch_instance(instance, account, options), Message::Launched)
 	}
 
 	pub fn update<'a>(&mut self, modal: &mut Option<Box<dyn Fn() -> Modal<'a, Message>>>, message: Message) -> Command<Message> {
@@ -40,52 +242,434 @@ impl MainUi {
 				Command::perform(self.mc_manager.clone().load_versions(), Message::VersionsLoaded)
 			}
 			Message::VersionsLoaded(versions) => {
-				if let Err(err) = versions {
-					*modal = Some(Box::new(move || Modal::with_foot(
-						text(err.to_string()).into(),
-						button(text("Retry")).on_press(Message::LoadVersions).into()
+				match versions {
+					Ok(versions) => {
+						self.version_options = versions.versions.iter().map(|v| v.id.clone()).collect();
+					}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::with_foot(
+							text(err.to_string()).into(),
+							button(text("Retry")).on_press(Message::LoadVersions).into()
+						)));
+					}
+				}
+				Command::none()
+			}
+			Message::LoadInstances => self.load_instances(),
+			Message::InstancesLoaded(res) => {
+				match res {
+					Ok(instances) => {
+						self.instances = instances;
+						if self.selected().is_none() {
+							self.selected_instance = self.instances.first().map(|instance| instance.id.clone());
+						}
+						Command::batch([self.load_instance_data(), self.load_instance_info()])
+					}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::with_foot(
+							text(format!("Failed to load the instances: {}", err)).into(),
+							button(text("Retry")).on_press(Message::LoadInstances).into()
+						)));
+						Command::none()
+					}
+				}
+			}
+			Message::InstanceInfoLoaded(info) => {
+				self.instance_info = info.into_iter().collect();
+				Command::none()
+			}
+			Message::FilterChanged(filter) => {
+				self.filter = filter;
+				Command::none()
+			}
+			Message::SortChanged(sort) => {
+				self.sort = sort;
+				Command::none()
+			}
+			Message::ToggleGroup(group) => {
+				if !self.collapsed_groups.remove(&group) {
+					self.collapsed_groups.insert(group);
+				}
+				Command::none()
+			}
+			Message::ToggleFavourite(id) => {
+				match self.instances.iter_mut().find(|instance| instance.id == id) {
+					Some(instance) => {
+						instance.favourite = !instance.favourite;
+						Command::perform(save_instance(instance.clone()), Message::InstanceSaved)
+					}
+					None => Command::none()
+				}
+			}
+			Message::InstanceSaved(res) => {
+				if let Err(err) = res {
+					*modal = Some(Box::new(move || Modal::new(
+						text(format!("Failed to save the instance: {}", err)).into()
 					)));
-				} else {
-					let guard = self.mc_manager.inner.blocking_lock();
-					let versions = guard.versions.as_ref().unwrap();
-					self.version_options = versions.versions.iter().map(|v| v.id.clone()).collect();
-					self.selected_version = Some(versions.latest.release.clone());
+					return self.load_instances();
+				}
+				Command::none()
+			}
+			Message::SelectInstance(id) => self.select_instance(id),
+			Message::LaunchInstance(id) => {
+				let select_cmd = self.select_instance(id);
+				Command::batch([select_cmd, self.launch(None)])
+			}
+			Message::OpenFolder(id) => {
+				match self.instances.iter().find(|instance| instance.id == id) {
+					Some(instance) => Command::perform(open_folder(instance_game_dir(instance)), Message::FolderOpened),
+					None => Command::none()
+				}
+			}
+			Message::FolderOpened(res) => {
+				if let Err(err) = res {
+					*modal = Some(Box::new(move || Modal::new(
+						text(format!("Failed to open the folder: {}", err)).into()
+					)));
+				}
+				Command::none()
+			}
+			Message::DuplicateInstance(id) => {
+				let instance = match self.instances.iter().find(|instance| instance.id == id) {
+					Some(instance) => instance.clone(),
+					None => return Command::none()
+				};
+				*modal = Some(Box::new(move || Modal::with_foot(
+					text(format!("Duplicate {}? Mods and resource packs share their data with the original where possible.", instance.name)).into(),
+					Row::new()
+						.push(button(text("Duplicate")).on_press(Message::ConfirmDuplicateInstance(instance.id.clone(), true)))
+						.push(button(text("Without saves")).on_press(Message::ConfirmDuplicateInstance(instance.id.clone(), false)))
+						.spacing(10)
+						.into()
+				).title("Duplicate instance")));
+				Command::none()
+			}
+			Message::ConfirmDuplicateInstance(id, include_saves) => {
+				*modal = None;
+				Command::perform(duplicate_instance(id, include_saves), Message::InstanceCreated)
+			}
+			Message::InstanceCreated(res) => {
+				match res {
+					Ok(instance) => {
+						let select_cmd = self.select_instance(instance.id);
+						Command::batch([select_cmd, self.load_instances()])
+					}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::new(
+							text(format!("Failed to create or copy the instance: {}", err)).into()
+						)));
+						Command::none()
+					}
 				}
+			}
+			Message::DeleteInstance(id) => {
+				let instance = match self.instances.iter().find(|instance| instance.id == id) {
+					Some(instance) => instance.clone(),
+					None => return Command::none()
+				};
+				*modal = Some(Box::new(move || Modal::with_foot(
+					text(format!("Delete {}? Its worlds, mods and settings are removed as well.", instance.name)).into(),
+					button(text("Delete")).on_press(Message::ConfirmDeleteInstance(instance.id.clone())).into()
+				).title("Delete instance")));
 				Command::none()
 			}
-			Message::VersionSelected(version) => {
-				self.selected_version = Some(version);
+			Message::ConfirmDeleteInstance(id) => {
+				*modal = None;
+				if self.is_running(&id) {
+					*modal = Some(Box::new(|| Modal::new(
+						text("The instance is running, stop it before deleting it").into()
+					)));
+					return Command::none();
+				}
+				if self.selected_instance.as_ref() == Some(&id) {
+					self.selected_instance = None;
+				}
+				Command::perform(delete_instance(id), Message::InstanceDeleted)
+			}
+			Message::InstanceDeleted(res) => {
+				if let Err(err) = res {
+					*modal = Some(Box::new(move || Modal::new(
+						text(format!("Failed to delete the instance: {}", err)).into()
+					)));
+				}
+				self.load_instances()
+			}
+			Message::CustomLogConfigToggled(value) => {
+				self.launch_options.custom_log_config = value;
 				Command::none()
 			}
-			Message::Play => {
-				Command::perform(self.mc_manager.clone().play_version(self.selected_version.as_ref().unwrap().clone()), Message::PlayFinished)
+			Message::Play => self.launch(None),
+			Message::ServerChanged(server) => {
+				self.server = server;
+				Command::none()
 			}
-			MainMessage::PlayFinished(res) => {
+			Message::WorldChanged(world) => {
+				self.world = world;
+				Command::none()
+			}
+			Message::QuickPlay(quick_play) => self.launch(Some(quick_play)),
+			Message::QuickPlayLogLoaded(res) => {
+				match res {
+					Ok(entries) => self.recent_quick_plays = entries,
+					Err(err) => eprintln!("error: failed to read the quick play log: {}", err)
+				}
+				Command::none()
+			}
+			Message::HistoryLoaded(res) => {
+				match res {
+					Ok(history) => self.history = history,
+					Err(err) => eprintln!("error: failed to read the launch history: {}", err)
+				}
+				Command::none()
+			}
+			Message::ExportHistory(format) => {
+				let dir = match &self.selected_instance {
+					Some(id) => instance_dir(id),
+					None => return Command::none()
+				};
+				Command::perform(async move { export_history(&dir, format).await }, Message::HistoryExported)
+			}
+			Message::HistoryExported(res) => {
+				self.export_status = Some(match res {
+					Ok(path) => format!("History exported to {}", path.display()),
+					Err(err) => format!("Failed to export the history: {}", err)
+				});
+				Command::none()
+			}
+			Message::TokenPlaceholderToggled(value) => {
+				self.token_placeholder = value;
+				Command::none()
+			}
+			Message::ExportScript => {
+				let (account, instance) = match (&self.account, &self.selected_instance) {
+					(Some(account), Some(instance)) => (account.clone(), instance.clone()),
+					_ => return Command::none()
+				};
+				self.export_status = Some("Preparing the launch script...".to_string());
+				Command::perform(self.mc_manager.clone().export_launch_script(
+					instance,
+					account,
+					self.launch_options.clone(),
+					self.token_placeholder
+				), Message::ScriptExported)
+			}
+			Message::ScriptExported(res) => {
+				self.export_status = Some(match res {
+					Ok(path) if self.token_placeholder => format!(
+						"Launch script written to {}, set {} before running it", path.display(), ACCESS_TOKEN_VAR
+					),
+					Ok(path) => format!("Launch script written to {}, it contains the access token", path.display()),
+					Err(err) => format!("Failed to export the launch script: {}", err)
+				});
+				Command::none()
+			}
+			Message::ExportFolderToggled(index, value) => {
+				self.export_folders[index] = value;
+				Command::none()
+			}
+			Message::ExportInstance => {
+				let instance = match &self.selected_instance {
+					Some(instance) => instance.clone(),
+					None => return Command::none()
+				};
+				let folders = ARCHIVE_FOLDERS.iter().zip(self.export_folders)
+					.filter(|(_, export)| *export)
+					.map(|(folder, _)| folder.to_string())
+					.collect();
+				self.export_status = Some("Exporting the instance...".to_string());
+				Command::perform(export_instance(instance, folders), Message::InstanceExported)
+			}
+			Message::InstanceExported(res) => {
+				self.export_status = Some(match res {
+					Ok(path) => format!("Instance exported to {}", path.display()),
+					Err(err) => format!("Failed to export the instance: {}", err)
+				});
+				Command::none()
+			}
+			Message::ShowLaunchCommand => {
+				let (account, instance) = match (&self.account, &self.selected_instance) {
+					(Some(account), Some(instance)) => (account.clone(), instance.clone()),
+					_ => return Command::none()
+				};
+				self.export_status = Some("Preparing the launch command...".to_string());
+				Command::perform(
+					self.mc_manager.clone().launch_command(instance, account, self.launch_options.clone()),
+					Message::LaunchCommandLoaded
+				)
+			}
+			Message::LaunchCommandLoaded(res) => {
+				self.export_status = None;
+				match res {
+					Ok(launch) => {
+						*modal = Some(Box::new(move || launch_command_modal(&launch)));
+					}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::new(
+							text(format!("Failed to prepare the launch command: {}", err)).into()
+						)));
+					}
+				}
 				Command::none()
 			}
+			Message::CopyText(text) => iced::clipboard::write(text),
+			Message::Launched(res) => {
+				self.launching -= 1;
+				match res {
+					Ok(game) => {
+						self.running.push(game.clone());
+						let id = game.id;
+						Command::batch([
+							Command::perform(async move { wait_for_crash(&game).await }, move |res| Message::GameExited(id, res)),
+							self.load_instance_data(),
+							self.load_instance_info()
+						])
+					}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::new(
+							text(format!("Failed to launch the game: {}", err)).into()
+						)));
+						Command::none()
+					}
+				}
+			}
+			Message::GameExited(id, res) => {
+				let is_selected = self.running.iter()
+					.any(|game| game.id == id && self.selected_instance.as_ref() == Some(&game.instance_id));
+				let data_cmd = if is_selected {
+					self.load_instance_data()
+				} else {
+					Command::none()
+				};
+				match res {
+					Ok(Some(report)) => {
+						*modal = Some(Box::new(move || crash_modal(id, &report)));
+					}
+					Ok(None) => {}
+					Err(err) => {
+						*modal = Some(Box::new(move || Modal::new(
+							text(format!("Failed to look for a crash report: {}", err)).into()
+						)));
+					}
+				}
+				Command::batch([data_cmd, self.load_instance_info()])
+			}
+			Message::StopGame(id) => {
+				match self.running.iter().find(|game| game.id == id) {
+					Some(game) => {
+						let game = game.clone();
+						Command::perform(async move { game.stop(STOP_TIMEOUT).await }, |_| Message::GameStopped)
+					}
+					None => Command::none()
+				}
+			}
+			Message::GameStopped | Message::Tick => Command::none(),
+			Message::DismissGame(id) => {
+				self.running.retain(|game| game.id != id);
+				Command::none()
+			}
+			Message::OpenConsole(_) | Message::OpenSettings(_) | Message::OpenNewInstance | Message::OpenImport => Command::none()
+		}
+	}
+
+	fn select_instance(&mut self, id: String) -> Command<Message> {
+		if self.selected_instance.as_ref() == Some(&id) {
+			return Command::none();
+		}
+		self.selected_instance = Some(id);
+		self.recent_quick_plays.clear();
+		self.history.clear();
+		self.export_status = None;
+		self.load_instance_data()
+	}
+
+	pub fn subscription(&self) -> Subscription<Message> {
+		// keeps the uptime of running games up to date
+		if self.running.iter().any(|game| !matches!(game.state(), GameState::Exited(_))) {
+			iced::time::every(Duration::from_secs(1)).map(|_| Message::Tick)
+		} else {
+			Subscription::none()
 		}
 	}
 
 	pub fn view(&self) -> Element<'_, MainMessage> {
 		let mut play_button = button("Play");
+		let mut export_script_button = button("Export launch script");
+		let mut show_command_button = button("Show launch command");
+		let mut export_instance_button = button("Export instance");
+		if let Some(instance) = self.selected() {
+			export_instance_button = export_instance_button.on_press(Message::ExportInstance);
+			play_button = button(text(format!("Play {}", instance.name))).on_press(Message::Play);
+			export_script_button = export_script_button.on_press(Message::ExportScript);
+			show_command_button = show_command_button.on_press(Message::ShowLaunchCommand);
+		}
 
-		let versions: Element<'_, MainMessage> = if !self.version_options.is_empty() {
-			let version_list: PickList<'_, String, Message, Renderer> = pick_list(
-				&self.version_options,
-				self.selected_version.clone(),
-				Message::VersionSelected
-			);
-			play_button = play_button.on_press(Message::Play);
-			version_list.into()
-		} else {
-			text("Loading versions...").into()
-		};
+		let list_header = Row::new()
+			.push(text_input("Filter instances", &self.filter).on_input(Message::FilterChanged))
+			.push(text("Sort by"))
+			.push(pick_list(&InstanceSort::ALL[..], Some(self.sort), Message::SortChanged))
+			.push(button("New instance").on_press(Message::OpenNewInstance))
+			.push(button("Import").on_press(Message::OpenImport))
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let custom_log_config = checkbox(
+			"Use launcher log configuration",
+			self.launch_options.custom_log_config,
+			Message::CustomLogConfigToggled
+		);
 
-		let content = Column::new()
-			.push(versions)
+		let mut join_server = button("Join server");
+		let mut open_world = button("Open world");
+		if self.selected_instance.is_some() {
+			if !self.server.is_empty() {
+				join_server = join_server.on_press(Message::QuickPlay(QuickPlay::Multiplayer(self.server.clone())));
+			}
+			if !self.world.is_empty() {
+				open_world = open_world.on_press(Message::QuickPlay(QuickPlay::Singleplayer(self.world.clone())));
+			}
+		}
+		let quick_play = Column::new()
+			.push(Row::new()
+				.push(text_input("server.example.com:25565", &self.server).on_input(Message::ServerChanged).width(Length::Fixed(240.0)))
+				.push(join_server)
+				.spacing(5))
+			.push(Row::new()
+				.push(text_input("World folder", &self.world).on_input(Message::WorldChanged).width(Length::Fixed(240.0)))
+				.push(open_world)
+				.spacing(5))
+			.spacing(5);
+
+		let mut content = Column::new()
+			.push(list_header)
+			.push(self.instances_view())
+			.push(custom_log_config)
 			.push(play_button)
+			.push(quick_play)
+			.push(Row::new()
+				.push(show_command_button)
+				.push(export_script_button)
+				.push(checkbox("Read the access token from ${MC_ACCESS_TOKEN}", self.token_placeholder, Message::TokenPlaceholderToggled))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(self.export_folders_view(export_instance_button))
+			.push(button("Console").on_press(Message::OpenConsole(None)))
+			.spacing(5)
 			.align_items(Alignment::Center);
 
+		if self.account.as_ref().is_some_and(|account| account.demo) {
+			content = content.push(text("This account doesn't own the game, it will be launched in demo mode"));
+		}
+		if !self.recent_quick_plays.is_empty() {
+			content = content.push(self.recent_quick_plays_view());
+		}
+		content = content.push(self.history_view());
+		if self.launching > 0 {
+			content = content.push(text(format!("Preparing {} game(s)...", self.launching)));
+		}
+		if !self.running.is_empty() {
+			content = content.push(self.running_view());
+		}
+
 		container(content)
 			.width(Length::Fill)
 			.height(Length::Fill)
@@ -93,4 +677,264 @@ impl MainUi {
 			.center_y()
 			.into()
 	}
+
+	fn instances_view(&self) -> Element<'_, Message> {
+		if self.instances.is_empty() {
+			return text("Create an instance to play").into();
+		}
+
+		let mut groups: BTreeMap<&str, Vec<&Instance>> = BTreeMap::new();
+		let mut ungrouped = Vec::new();
+		for instance in self.visible_instances() {
+			match &instance.group {
+				Some(group) => groups.entry(group.as_str()).or_default().push(instance),
+				None => ungrouped.push(instance)
+			}
+		}
+
+		let mut list = Column::new().spacing(5);
+		let has_groups = !groups.is_empty();
+		for (group, instances) in groups {
+			let collapsed = self.collapsed_groups.contains(group);
+			list = list.push(button(text(format!("{} {} ({})", if collapsed { "+" } else { "-" }, group, instances.len())))
+				.on_press(Message::ToggleGroup(group.to_string()))
+				.style(iced::theme::Button::Text));
+			if !collapsed {
+				for instance in instances {
+					list = list.push(self.instance_row(instance));
+				}
+			}
+		}
+		if has_groups && !ungrouped.is_empty() {
+			list = list.push(text(format!("Ungrouped ({})", ungrouped.len())));
+		}
+		for instance in ungrouped {
+			list = list.push(self.instance_row(instance));
+		}
+		scrollable(list)
+			.height(Length::Fixed(280.0))
+			.into()
+	}
+
+	fn instance_row<'a>(&'a self, instance: &'a Instance) -> Element<'a, Message> {
+		let info = self.instance_info.get(&instance.id);
+		let status = if self.is_running(&instance.id) {
+			"running"
+		} else if info.is_some_and(|info| info.installed) {
+			"installed"
+		} else {
+			"needs install"
+		};
+		let last_played = match info.and_then(|info| info.last_played) {
+			Some(time) => format!("played {}", format_ago(time)),
+			None => "never played".to_string()
+		};
+		let selected = self.selected_instance.as_ref() == Some(&instance.id);
+		let icon: Element<'a, Message> = match instance_icon(instance) {
+			Some(path) => image(path).width(Length::Fixed(32.0)).height(Length::Fixed(32.0)).into(),
+			None => text(instance.name.chars().next().unwrap_or('?').to_uppercase().to_string()).size(24).into()
+		};
+
+		let name = if instance.favourite { format!("* {}", instance.name) } else { instance.name.clone() };
+		let mut details = Column::new()
+			.push(text(if selected { format!("> {}", name) } else { name }))
+			.push(text(format!("{} {} - {} - {}", instance.loader, instance.version, last_played, status)).size(12));
+		if !instance.notes.is_empty() {
+			details = details.push(text(instance.notes.lines().next().unwrap_or_default()).size(12));
+		}
+		Row::new()
+			.push(container(icon)
+				.width(Length::Fixed(40.0))
+				.center_x())
+			.push(button(details)
+				.on_press(Message::SelectInstance(instance.id.clone()))
+				.style(iced::theme::Button::Text)
+				.width(Length::Fill))
+			.push(button("Play").on_press(Message::LaunchInstance(instance.id.clone())))
+			.push(button(if instance.favourite { "Unfavourite" } else { "Favourite" })
+				.on_press(Message::ToggleFavourite(instance.id.clone())))
+			.push(button("Edit").on_press(Message::OpenSettings(instance.id.clone())))
+			.push(button("Folder").on_press(Message::OpenFolder(instance.id.clone())))
+			.push(button("Duplicate").on_press(Message::DuplicateInstance(instance.id.clone())))
+			.push(button("Delete").on_press(Message::DeleteInstance(instance.id.clone())))
+			.spacing(5)
+			.align_items(Alignment::Center)
+			.into()
+	}
+
+	fn export_folders_view<'a>(&'a self, export_button: Button<'a, Message, Renderer>) -> Element<'a, Message> {
+		let mut row = Row::new()
+			.push(export_button)
+			.spacing(10)
+			.align_items(Alignment::Center);
+		for (index, folder) in ARCHIVE_FOLDERS.iter().enumerate() {
+			row = row.push(checkbox(*folder, self.export_folders[index], move |value| Message::ExportFolderToggled(index, value)));
+		}
+		row.into()
+	}
+
+	fn history_view(&self) -> Element<'_, Message> {
+		if self.selected_instance.is_none() {
+			return Column::new().into();
+		}
+
+		let summary = match self.history.last() {
+			Some(last) => {
+				let playtime = total_playtime(&self.history).as_secs();
+				format!(
+					"Played {}h {:02}m over {} launch(es), last played {}",
+					playtime / 3600, playtime / 60 % 60, self.history.len(), format_unix_millis(last.started_at)
+				)
+			}
+			None => "Never played".to_string()
+		};
+
+		let mut history = Column::new()
+			.push(Row::new()
+				.push(text(summary))
+				.push(button("Export CSV").on_press(Message::ExportHistory(HistoryFormat::Csv)))
+				.push(button("Export JSON").on_press(Message::ExportHistory(HistoryFormat::Json)))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.spacing(5)
+			.align_items(Alignment::Center);
+		if let Some(status) = &self.export_status {
+			history = history.push(text(status));
+		}
+		history.into()
+	}
+
+	fn recent_quick_plays_view(&self) -> Element<'_, Message> {
+		let mut recent = Row::new()
+			.push(text("Recently played:"))
+			.spacing(5)
+			.align_items(Alignment::Center);
+		for entry in self.recent_quick_plays.iter().take(5) {
+			let quick_play = match entry.t.as_str() {
+				"singleplayer" => QuickPlay::Singleplayer(entry.id.clone()),
+				"multiplayer" => QuickPlay::Multiplayer(entry.id.clone()),
+				"realms" => QuickPlay::Realms(entry.id.clone()),
+				_ => continue
+			};
+			let name = if entry.name.is_empty() { &entry.id } else { &entry.name };
+			let mut entry_button = button(text(name));
+			if self.selected_instance.is_some() {
+				entry_button = entry_button.on_press(Message::QuickPlay(quick_play));
+			}
+			recent = recent.push(entry_button);
+		}
+		recent.into()
+	}
+
+	fn running_view(&self) -> Element<'_, Message> {
+		let mut list = Column::new()
+			.push(text("Running games"))
+			.spacing(5);
+		for game in &self.running {
+			let state = game.state();
+			let status = match state {
+				GameState::Starting => "starting".to_string(),
+				GameState::Running => "running".to_string(),
+				GameState::Exited(Some(code)) => format!("exited with code {}", code),
+				GameState::Exited(None) => "terminated".to_string()
+			};
+			let pid = game.pid.map_or_else(|| "?".to_string(), |pid| pid.to_string());
+			let uptime = game.uptime().as_secs();
+			let name = self.instances.iter()
+				.find(|instance| instance.id == game.instance_id)
+				.map_or(game.instance_id.as_str(), |instance| instance.name.as_str());
+			let mut row = Row::new()
+				.push(text(format!(
+					"{} {} ({}), PID {}, up {}:{:02}:{:02}",
+					name, game.version, status, pid, uptime / 3600, uptime / 60 % 60, uptime % 60
+				)))
+				.push(button("Console").on_press(Message::OpenConsole(Some(game.id))))
+				.spacing(10)
+				.align_items(Alignment::Center);
+			if matches!(state, GameState::Exited(_)) {
+				row = row.push(button("Dismiss").on_press(Message::DismissGame(game.id)));
+			} else {
+				row = row.push(button("Stop").on_press(Message::StopGame(game.id)));
+			}
+			list = list.push(row);
+		}
+		list.into()
+	}
+}
+
+/// Formats a time in milliseconds since the unix epoch relative to now, e.g. `3 days ago`.
+fn format_ago(millis: u64) -> String {
+	let seconds = unix_millis(SystemTime::now()).saturating_sub(millis) / 1000;
+	match seconds {
+		0..=59 => "just now".to_string(),
+		60..=3599 => format!("{} min ago", seconds / 60),
+		3600..=86399 => format!("{} h ago", seconds / 3600),
+		86400..=172799 => "1 day ago".to_string(),
+		_ => format!("{} days ago", seconds / 86400)
+	}
+}
+
+fn launch_command_modal<'a>(launch: &LaunchCommand) -> Modal<'a, Message> {
+	let line = command_line(launch);
+
+	let mut body = Column::new()
+		.push(text(format!("Working directory: {}", launch.working_dir.display())))
+		.spacing(10);
+	if !launch.env.is_empty() {
+		body = body.push(text("Environment:"));
+		for (name, value) in &launch.env {
+			body = body.push(text(match value {
+				Some(value) => format!("  {}={}", name, value),
+				None => format!("  unset {}", name)
+			}).size(12));
+		}
+	}
+	let mut arguments = Column::new()
+		.push(text(launch.wrapper.iter().chain([&launch.program]).cloned().collect::<Vec<_>>().join(" ")).size(12));
+	for argument in &launch.args {
+		arguments = arguments.push(text(format!("  {}", argument)).size(12));
+	}
+	body = body
+		.push(text("Command:"))
+		.push(scrollable(arguments).height(Length::Fixed(320.0)));
+
+	let foot = Row::new()
+		.push(button("Copy").on_press(Message::CopyText(line)))
+		.spacing(10);
+
+	Modal::with_foot(body.into(), foot.into())
+		.title("Launch command")
+		.size(720.0, 560.0)
+}
+
+fn crash_modal<'a>(game_id: usize, report: &CrashReport) -> Modal<'a, Message> {
+	let summary = match report.exit_code {
+		Some(code) => format!("The game exited with code {}", code),
+		None => "The game was terminated".to_string()
+	};
+
+	let mut body = Column::new()
+		.push(text(summary))
+		.spacing(10);
+	if report.causes.is_empty() {
+		body = body.push(text("No known cause was detected, check the console for details"));
+	} else {
+		body = body.push(text("Probable cause:"));
+		for cause in &report.causes {
+			body = body.push(text(cause));
+		}
+	}
+	if let Some((path, contents)) = &report.file {
+		body = body
+			.push(text(path.display().to_string()))
+			.push(scrollable(text(contents).size(12)).height(Length::Fixed(240.0)));
+	}
+
+	let foot = Row::new()
+		.push(button("Open console").on_press(Message::OpenConsole(Some(game_id))))
+		.spacing(10);
+
+	Modal::with_foot(body.into(), foot.into())
+		.title("Game crashed")
+		.size(720.0, 560.0)
 }
@@ -1,22 +1,133 @@
+use std::path::PathBuf;
 use std::sync::Arc;
-use tokio::sync::Mutex;
-use crate::backend::{McDownloader, McManager, McResult};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::SystemTime;
+use tokio::sync::{broadcast, Mutex};
+use crate::backend::{finish_launch, import_archive, game_logs_dir, GameEvent, GameProcess, import_atlauncher_instances, import_gdlauncher_instances, import_official_profiles, import_prism_instances, ImportReport, instance_dir, LaunchCommand, launch_script, LaunchOptions, load_instance, LoaderVersion, McDownloader, McManager, McResult, record_launch, run_hook, unix_millis, VersionDetails, write_launch_script};
+use crate::model::{Account, all_versions, LaunchRecord, ModLoader};
 
 #[derive(Clone)]
 pub struct UiManagerWrapper {
-	pub inner: Arc<Mutex<McManager>>
+	pub inner: Arc<Mutex<McManager>>,
+	game_events: broadcast::Sender<GameEvent>,
+	next_game_id: Arc<AtomicUsize>
 }
 
 impl UiManagerWrapper {
 	pub fn new(mc_downloader: McDownloader) -> Self {
-		Self { inner: Arc::new(Mutex::new(McManager::new(mc_downloader))) }
+		let (game_events, _) = broadcast::channel(1024);
+		Self {
+			inner: Arc::new(Mutex::new(McManager::new(mc_downloader))),
+			game_events,
+			next_game_id: Arc::new(AtomicUsize::new(0))
+		}
 	}
 
-	pub async fn load_versions(self) -> McResult<()> {
+	/// Subscribes to the output and state changes of every game launched through this manager
+	/// without having to lock it.
+	pub fn subscribe_game_events(&self) -> broadcast::Receiver<GameEvent> {
+		self.game_events.subscribe()
+	}
+
+	pub async fn load_versions(self) -> McResult<all_versions::Versions> {
 		self.inner.lock().await.load_versions().await
 	}
 
-	pub async fn play_version(self, version: String) -> McResult<()> {
-		self.inner.lock().await.play_version(&version).await
+	pub async fn version_details(self, version: String) -> McResult<VersionDetails> {
+		self.inner.lock().await.version_details(&version).await
+	}
+
+	pub async fn loader_versions(self, loader: ModLoader, version: String) -> McResult<Vec<LoaderVersion>> {
+		self.inner.lock().await.loader_versions(loader, &version).await
+	}
+
+	/// Imports the profiles of the official launcher in `dir`, see [`import_official_profiles`].
+	pub async fn import_official(self, dir: PathBuf, reuse_files: bool) -> McResult<ImportReport> {
+		let versions = self.inner.lock().await.load_versions().await?;
+		import_official_profiles(&dir, &versions.latest, reuse_files).await
+	}
+
+	pub async fn import_prism(self, dir: PathBuf, move_files: bool) -> McResult<ImportReport> {
+		import_prism_instances(&dir, move_files).await
+	}
+
+	pub async fn import_atlauncher(self, dir: PathBuf, move_files: bool) -> McResult<ImportReport> {
+		import_atlauncher_instances(&dir, move_files).await
+	}
+
+	pub async fn import_gdlauncher(self, dir: PathBuf, move_files: bool) -> McResult<ImportReport> {
+		import_gdlauncher_instances(&dir, move_files).await
+	}
+
+	/// Imports an instance archive and downloads the game files it needs.
+	pub async fn import_archive(self, path: PathBuf) -> McResult<ImportReport> {
+		let instance = import_archive(path).await?;
+		let mut report = ImportReport::default();
+		if let Err(err) = self.inner.lock().await.install(&instance).await {
+			report.warnings.push(format!("{}: failed to download the game files, they'll be downloaded at launch: {}", instance.name, err));
+		}
+		report.instances.push(instance);
+		Ok(report)
+	}
+
+	/// Installs the version of an instance and returns the command [`Self::launch_instance`]
+	/// would run, with the access token redacted.
+	pub async fn launch_command(self, instance_id: String, account: Account, options: LaunchOptions) -> McResult<LaunchCommand> {
+		let instance = load_instance(&instance_id).await?;
+		let launch = self.inner.lock().await.prepare_launch(&instance, &account, &options).await?;
+		Ok(launch.redacted())
+	}
+
+	/// Installs the version of an instance and writes a shell script that launches it like
+	/// [`Self::launch_instance`] would, returning the path of the script.
+	pub async fn export_launch_script(self, instance_id: String, account: Account, options: LaunchOptions, token_placeholder: bool) -> McResult<PathBuf> {
+		let instance = load_instance(&instance_id).await?;
+		let launch = self.inner.lock().await.prepare_launch(&instance, &account, &options).await?;
+		let script = launch_script(&launch, &account.mc_creds.access_token, token_placeholder);
+		write_launch_script(&instance_dir(&instance.id), &launch.version, &script).await
+	}
+
+	/// Installs and starts an instance. The manager is only locked until the game is prepared,
+	/// so several games can run at the same time.
+	pub async fn launch_instance(self, instance_id: String, account: Account, options: LaunchOptions) -> McResult<Arc<GameProcess>> {
+		let instance = load_instance(&instance_id).await?;
+		let launch = self.inner.lock().await.prepare_launch(&instance, &account, &options).await?;
+
+		if let Some(command) = &instance.settings.pre_launch_command {
+			run_hook(command, &launch, None).await?;
+		}
+
+		let id = self.next_game_id.fetch_add(1, Ordering::Relaxed);
+		let game = GameProcess::spawn(id, &launch, &game_logs_dir(&instance.id), self.game_events.clone()).await?;
+
+		let history_dir = instance_dir(&instance.id);
+		let started_at = unix_millis(game.started_at);
+		let record = LaunchRecord {
+			started_at,
+			ended_at: None,
+			exit_code: None,
+			account: account.name,
+			version: launch.version.clone(),
+			loader: instance.loader.id().to_string()
+		};
+		if let Err(err) = record_launch(&history_dir, record).await {
+			eprintln!("error: failed to record the launch: {}", err);
+		}
+
+		let waiting_game = game.clone();
+		tokio::spawn(async move {
+			let code = waiting_game.wait().await;
+			if let Err(err) = finish_launch(&history_dir, started_at, unix_millis(SystemTime::now()), code).await {
+				eprintln!("error: failed to record the end of the launch: {}", err);
+			}
+			if let Some(command) = instance.settings.post_exit_command {
+				// like shells report it, so the hook always gets a code
+				let hook_code = code.or_else(|| waiting_game.exit_signal().map(|signal| 128 + signal));
+				if let Err(err) = run_hook(&command, &launch, hook_code).await {
+					eprintln!("error: post-exit command failed: {}", err);
+				}
+			}
+		});
+		Ok(game)
 	}
 }
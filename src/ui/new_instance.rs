@@ -0,0 +1,326 @@
+use iced::{Alignment, Color, Command, Length};
+use iced::widget::{button, checkbox, Column, container, horizontal_space, pick_list, Row, scrollable, text, text_input};
+use crate::backend::{create_instance, LoaderVersion, McResult, VersionDetails};
+use crate::model::{all_versions, Instance, ModLoader};
+use crate::ui::Element;
+use crate::ui::manager::UiManagerWrapper;
+
+#[derive(Debug, Clone)]
+pub enum NewInstanceMessage {
+	LoadVersions,
+	VersionsLoaded(McResult<all_versions::Versions>),
+	TypeToggled(usize, bool),
+	SearchChanged(String),
+	NameChanged(String),
+	VersionSelected(String),
+	DetailsLoaded(String, McResult<VersionDetails>),
+	LoaderSelected(ModLoader),
+	LoaderVersionsLoaded(ModLoader, String, McResult<Vec<LoaderVersion>>),
+	LoaderVersionSelected(String),
+	Create,
+	Created(McResult<Instance>),
+	Cancel
+}
+
+type Message = NewInstanceMessage;
+
+/// Values of `all_versions::Version::t` with their label, in the order of the filter checkboxes.
+const VERSION_TYPES: [(&str, &str); 4] = [
+	("release", "Releases"),
+	("snapshot", "Snapshots"),
+	("old_beta", "Beta"),
+	("old_alpha", "Alpha")
+];
+/// Loaders instances can be created with, the others can't be launched yet.
+const LOADERS: [ModLoader; 3] = [ModLoader::Vanilla, ModLoader::Fabric, ModLoader::Quilt];
+
+pub struct NewInstanceUi {
+	mc_manager: Option<UiManagerWrapper>,
+	versions: Vec<all_versions::Version>,
+	/// Which of [`VERSION_TYPES`] are listed.
+	shown_types: [bool; 4],
+	search: String,
+	name: String,
+	selected_version: Option<String>,
+	details: Option<McResult<VersionDetails>>,
+	loader: ModLoader,
+	loader_versions: Vec<String>,
+	/// `None` picks the newest stable loader version at launch.
+	loader_version: Option<String>,
+	status: Option<String>,
+	creating: bool
+}
+
+impl NewInstanceUi {
+	pub fn new() -> Self {
+		Self {
+			mc_manager: None,
+			versions: Vec::new(),
+			shown_types: [true, false, false, false],
+			search: String::new(),
+			name: String::new(),
+			selected_version: None,
+			details: None,
+			loader: ModLoader::Vanilla,
+			loader_versions: Vec::new(),
+			loader_version: None,
+			status: None,
+			creating: false
+		}
+	}
+
+	/// Resets the wizard and loads the version list through `mc_manager`.
+	pub fn load(&mut self, mc_manager: UiManagerWrapper) -> Command<Message> {
+		*self = Self {
+			mc_manager: Some(mc_manager),
+			..Self::new()
+		};
+		self.update(Message::LoadVersions)
+	}
+
+	fn manager(&self) -> UiManagerWrapper {
+		self.mc_manager.clone().expect("the wizard is used before it was loaded")
+	}
+
+	fn load_loader_versions(&mut self) -> Command<Message> {
+		self.loader_versions.clear();
+		self.loader_version = None;
+		let version = match &self.selected_version {
+			Some(version) if self.loader != ModLoader::Vanilla => version.clone(),
+			_ => return Command::none()
+		};
+		let loader = self.loader;
+		Command::perform(
+			self.manager().loader_versions(loader, version.clone()),
+			move |res| Message::LoaderVersionsLoaded(loader, version, res)
+		)
+	}
+
+	pub fn update(&mut self, message: Message) -> Command<Message> {
+		match message {
+			Message::LoadVersions => {
+				self.status = Some("Loading versions...".to_string());
+				Command::perform(self.manager().load_versions(), Message::VersionsLoaded)
+			}
+			Message::VersionsLoaded(res) => {
+				match res {
+					Ok(versions) => {
+						self.status = None;
+						self.versions = versions.versions;
+						return self.update(Message::VersionSelected(versions.latest.release));
+					}
+					Err(err) => self.status = Some(format!("Failed to load the versions: {}", err))
+				}
+				Command::none()
+			}
+			Message::TypeToggled(index, value) => {
+				self.shown_types[index] = value;
+				Command::none()
+			}
+			Message::SearchChanged(search) => {
+				self.search = search;
+				Command::none()
+			}
+			Message::NameChanged(name) => {
+				self.name = name;
+				Command::none()
+			}
+			Message::VersionSelected(version) => {
+				if self.selected_version.as_ref() == Some(&version) {
+					return Command::none();
+				}
+				self.selected_version = Some(version.clone());
+				self.details = None;
+				let details_cmd = Command::perform(
+					self.manager().version_details(version.clone()),
+					move |res| Message::DetailsLoaded(version, res)
+				);
+				Command::batch([details_cmd, self.load_loader_versions()])
+			}
+			Message::DetailsLoaded(version, res) => {
+				// the selection may have changed while the version JSON was downloaded
+				if self.selected_version.as_ref() == Some(&version) {
+					self.details = Some(res);
+				}
+				Command::none()
+			}
+			Message::LoaderSelected(loader) => {
+				self.loader = loader;
+				self.load_loader_versions()
+			}
+			Message::LoaderVersionsLoaded(loader, version, res) => {
+				if self.loader != loader || self.selected_version.as_ref() != Some(&version) {
+					return Command::none();
+				}
+				match res {
+					Ok(versions) if versions.is_empty() => {
+						self.status = Some(format!("{} doesn't support {}", loader, version));
+					}
+					Ok(versions) => {
+						self.status = None;
+						self.loader_versions = versions.into_iter()
+							.map(|loader_version| loader_version.version)
+							.collect();
+					}
+					Err(err) => self.status = Some(format!("Failed to load the {} versions: {}", loader, err))
+				}
+				Command::none()
+			}
+			Message::LoaderVersionSelected(version) => {
+				self.loader_version = Some(version);
+				Command::none()
+			}
+			Message::Create => {
+				let version = match &self.selected_version {
+					Some(version) => version.clone(),
+					None => return Command::none()
+				};
+				let name = match self.name.trim() {
+					"" if self.loader == ModLoader::Vanilla => version.clone(),
+					"" => format!("{} {}", self.loader, version),
+					name => name.to_string()
+				};
+				self.creating = true;
+				Command::perform(
+					create_instance(name, version, self.loader, self.loader_version.clone()),
+					Message::Created
+				)
+			}
+			Message::Created(res) => {
+				self.creating = false;
+				if let Err(err) = res {
+					self.status = Some(format!("Failed to create the instance: {}", err));
+				}
+				Command::none()
+			}
+			Message::Cancel => Command::none()
+		}
+	}
+
+	/// Versions of the shown types whose id contains the search text, newest first.
+	fn visible_versions(&self) -> impl Iterator<Item = &all_versions::Version> {
+		let search = self.search.trim().to_lowercase();
+		self.versions.iter().filter(move |version| {
+			VERSION_TYPES.iter().zip(self.shown_types)
+				.any(|((t, _), shown)| shown && version.t == *t)
+				&& version.id.to_lowercase().contains(&search)
+		})
+	}
+
+	pub fn view(&self) -> Element<'_, Message> {
+		let mut create_button = button("Create");
+		if self.selected_version.is_some() && !self.creating {
+			create_button = create_button.on_press(Message::Create);
+		}
+		let header = Row::new()
+			.push(button("Cancel").on_press(Message::Cancel))
+			.push(text("New instance").size(20))
+			.push(horizontal_space(Length::Fill))
+			.push(create_button)
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let mut filters = Row::new()
+			.push(text_input("Search versions", &self.search)
+				.on_input(Message::SearchChanged)
+				.width(Length::Fixed(240.0)))
+			.spacing(10)
+			.align_items(Alignment::Center);
+		for (index, (_, label)) in VERSION_TYPES.iter().enumerate() {
+			filters = filters.push(checkbox(*label, self.shown_types[index], move |value| Message::TypeToggled(index, value)));
+		}
+
+		let mut list = Column::new().spacing(2);
+		for version in self.visible_versions() {
+			let selected = self.selected_version.as_ref() == Some(&version.id);
+			let label = if selected { format!("> {}", version.id) } else { version.id.clone() };
+			list = list.push(button(Row::new()
+					.push(text(label).width(Length::Fill))
+					.push(text(&version.t).size(12)))
+				.on_press(Message::VersionSelected(version.id.clone()))
+				.style(iced::theme::Button::Text)
+				.width(Length::Fill));
+		}
+
+		let versions = Column::new()
+			.push(filters)
+			.push(scrollable(list).height(Length::Fill))
+			.spacing(10)
+			.width(Length::FillPortion(1));
+
+		let mut content = Column::new()
+			.push(header)
+			.spacing(10)
+			.padding(10);
+		if let Some(status) = &self.status {
+			content = content.push(text(status).style(Color::from_rgb8(0xE0, 0x6C, 0x75)));
+		}
+		content = content.push(Row::new()
+			.push(versions)
+			.push(self.details_view())
+			.spacing(20)
+			.height(Length::Fill));
+
+		container(content)
+			.width(Length::Fill)
+			.height(Length::Fill)
+			.into()
+	}
+
+	fn details_view(&self) -> Element<'_, Message> {
+		let mut details = Column::new()
+			.spacing(10)
+			.width(Length::FillPortion(1));
+		let version = match self.selected_version.as_ref()
+			.and_then(|id| self.versions.iter().find(|version| &version.id == id)) {
+			Some(version) => version,
+			None => return details.push(text("Select a version")).into()
+		};
+
+		details = details
+			.push(text(&version.id).size(20))
+			.push(text(format!("Released {}", version.release_time.get(..10).unwrap_or(&version.release_time))));
+		details = match &self.details {
+			Some(Ok(version_details)) => details
+				.push(text(format!("Requires Java {}", version_details.java_version)))
+				.push(text(format!("Download size about {}", format_size(version_details.download_size)))),
+			Some(Err(err)) => details.push(text(format!("Failed to load the version details: {}", err))),
+			None => details.push(text("Loading version details..."))
+		};
+
+		let mut loader = Row::new()
+			.push(text("Mod loader").width(Length::Fixed(120.0)))
+			.push(pick_list(&LOADERS[..], Some(self.loader), Message::LoaderSelected))
+			.spacing(10)
+			.align_items(Alignment::Center);
+		if self.loader != ModLoader::Vanilla {
+			loader = loader.push(pick_list(&self.loader_versions[..], self.loader_version.clone(), Message::LoaderVersionSelected)
+				.placeholder("Latest stable"));
+		}
+
+		details
+			.push(loader)
+			.push(Row::new()
+				.push(text("Name").width(Length::Fixed(120.0)))
+				.push(text_input(&version.id, &self.name).on_input(Message::NameChanged))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.into()
+	}
+}
+
+/// Formats a byte count for humans, e.g. `412.5 MB`.
+fn format_size(bytes: u64) -> String {
+	const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+	let mut size = bytes as f64;
+	let mut unit = 0;
+	while size >= 1000.0 && unit < UNITS.len() - 1 {
+		size /= 1000.0;
+		unit += 1;
+	}
+	if unit == 0 {
+		format!("{} {}", bytes, UNITS[0])
+	} else {
+		format!("{:.1} {}", size, UNITS[unit])
+	}
+}
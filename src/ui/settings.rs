@@ -0,0 +1,377 @@
+use std::path::PathBuf;
+use iced::{Alignment, Color, Command, Length};
+use iced::widget::{button, checkbox, Column, container, horizontal_space, image, pick_list, Row, scrollable, text, text_input};
+use crate::backend::{import_instance_icon, instance_icon, list_mod_files, load_instance, McResult, remove_instance_icon, save_instance, use_mod_icon};
+use crate::model::{EnvAction, EnvVar, Instance};
+use crate::ui::Element;
+
+#[derive(Debug, Clone)]
+pub enum SettingsMessage {
+	Loaded(McResult<Instance>),
+	NameChanged(String),
+	GroupChanged(String),
+	NotesChanged(String),
+	FavouriteToggled(bool),
+	ModFilesLoaded(McResult<Vec<String>>),
+	IconPathChanged(String),
+	ImportIcon,
+	ModIconSelected(String),
+	UseModIcon,
+	RemoveIcon,
+	IconChanged(McResult<Instance>),
+	WrapperChanged(String),
+	PreLaunchChanged(String),
+	PostExitChanged(String),
+	JavaPathChanged(String),
+	JvmArgsChanged(String),
+	GameDirChanged(String),
+	WidthChanged(String),
+	HeightChanged(String),
+	FullscreenToggled(bool),
+	EnvAdded,
+	EnvRemoved(usize),
+	EnvNameChanged(usize, String),
+	EnvActionChanged(usize, EnvAction),
+	EnvValueChanged(usize, String),
+	Save,
+	Saved(McResult<()>),
+	Back
+}
+
+type Message = SettingsMessage;
+
+pub struct SettingsUi {
+	instance: Option<Instance>,
+	status: Option<Result<String, String>>,
+	/// Path of a PNG to use as the icon.
+	icon_path: String,
+	/// Mod jars in the game directory, for taking the icon from one.
+	mod_files: Vec<String>,
+	selected_mod: Option<String>
+}
+
+impl SettingsUi {
+	pub fn new() -> Self {
+		Self { instance: None, status: None, icon_path: String::new(), mod_files: Vec::new(), selected_mod: None }
+	}
+
+	pub fn load(&mut self, instance_id: String) -> Command<Message> {
+		*self = Self::new();
+		Command::perform(async move { load_instance(&instance_id).await }, Message::Loaded)
+	}
+
+	pub fn update(&mut self, message: Message) -> Command<Message> {
+		match message {
+			Message::Loaded(res) => {
+				match res {
+					Ok(instance) => {
+						let mods_instance = instance.clone();
+						self.instance = Some(instance);
+						return Command::perform(async move { list_mod_files(&mods_instance).await }, Message::ModFilesLoaded);
+					}
+					Err(err) => self.status = Some(Err(format!("Failed to load the instance: {}", err)))
+				}
+				Command::none()
+			}
+			Message::ModFilesLoaded(res) => {
+				match res {
+					Ok(files) => self.mod_files = files,
+					Err(err) => eprintln!("error: failed to list the mods: {}", err)
+				}
+				Command::none()
+			}
+			Message::IconPathChanged(path) => {
+				self.icon_path = path;
+				Command::none()
+			}
+			Message::ModIconSelected(file) => {
+				self.selected_mod = Some(file);
+				Command::none()
+			}
+			Message::ImportIcon | Message::UseModIcon | Message::RemoveIcon => {
+				let id = match &self.instance {
+					Some(instance) => instance.id.clone(),
+					None => return Command::none()
+				};
+				match message {
+					Message::ImportIcon => Command::perform(
+						import_instance_icon(id, PathBuf::from(self.icon_path.trim())),
+						Message::IconChanged
+					),
+					Message::UseModIcon => match &self.selected_mod {
+						Some(file) => Command::perform(use_mod_icon(id, file.clone()), Message::IconChanged),
+						None => Command::none()
+					},
+					_ => Command::perform(remove_instance_icon(id), Message::IconChanged)
+				}
+			}
+			Message::IconChanged(res) => {
+				match res {
+					// the rest of the instance may have unsaved changes
+					Ok(updated) => if let Some(instance) = &mut self.instance {
+						instance.icon = updated.icon;
+						self.icon_path.clear();
+					},
+					Err(err) => self.status = Some(Err(format!("Failed to change the icon: {}", err)))
+				}
+				Command::none()
+			}
+			Message::Save => {
+				match &self.instance {
+					Some(instance) => Command::perform(save_instance(instance.clone()), Message::Saved),
+					None => Command::none()
+				}
+			}
+			Message::Saved(res) => {
+				self.status = Some(match res {
+					Ok(()) => Ok("Settings saved".to_string()),
+					Err(err) => Err(format!("Failed to save the settings: {}", err))
+				});
+				Command::none()
+			}
+			Message::Back => Command::none(),
+			message => {
+				if let Some(instance) = &mut self.instance {
+					let settings = &mut instance.settings;
+					match message {
+						Message::NameChanged(name) => instance.name = name,
+						Message::GroupChanged(group) => instance.group = non_empty(group),
+						Message::NotesChanged(notes) => instance.notes = notes,
+						Message::FavouriteToggled(value) => instance.favourite = value,
+						Message::WrapperChanged(value) => settings.wrapper_command = non_empty(value),
+						Message::PreLaunchChanged(value) => settings.pre_launch_command = non_empty(value),
+						Message::PostExitChanged(value) => settings.post_exit_command = non_empty(value),
+						Message::JavaPathChanged(value) => settings.java_path = non_empty(value),
+						Message::JvmArgsChanged(value) => settings.jvm_args = non_empty(value),
+						Message::GameDirChanged(value) => settings.game_dir = non_empty(value).map(PathBuf::from),
+						Message::WidthChanged(value) => match parse_size(&value) {
+							Some(width) => settings.width = width,
+							None => return Command::none()
+						},
+						Message::HeightChanged(value) => match parse_size(&value) {
+							Some(height) => settings.height = height,
+							None => return Command::none()
+						},
+						Message::FullscreenToggled(value) => settings.fullscreen = value,
+						Message::EnvAdded => settings.env.push(EnvVar {
+							name: String::new(),
+							action: EnvAction::Set,
+							value: String::new()
+						}),
+						Message::EnvRemoved(index) => {
+							settings.env.remove(index);
+						}
+						Message::EnvNameChanged(index, name) => settings.env[index].name = name,
+						Message::EnvActionChanged(index, action) => settings.env[index].action = action,
+						Message::EnvValueChanged(index, value) => settings.env[index].value = value,
+						_ => unreachable!()
+					}
+					self.status = None;
+				}
+				Command::none()
+			}
+		}
+	}
+
+	pub fn view(&self) -> Element<'_, Message> {
+		let header = Row::new()
+			.push(button("Back").on_press(Message::Back))
+			.push(text("Instance settings").size(20))
+			.push(horizontal_space(Length::Fill))
+			.push(button("Save").on_press(Message::Save))
+			.spacing(10)
+			.align_items(Alignment::Center);
+
+		let mut content = Column::new()
+			.push(header)
+			.spacing(10)
+			.padding(10);
+
+		match &self.status {
+			Some(Ok(status)) => content = content.push(text(status)),
+			Some(Err(err)) => content = content.push(text(err).style(Color::from_rgb8(0xE0, 0x6C, 0x75))),
+			None => {}
+		}
+
+		let instance = match &self.instance {
+			Some(instance) => instance,
+			None if self.status.is_some() => return container(content).into(),
+			None => return container(content.push(text("Loading settings..."))).into()
+		};
+		let settings = &instance.settings;
+
+		let form = Column::new()
+			.push(Row::new()
+				.push(text("Name").width(Length::Fixed(200.0)))
+				.push(text_input("Instance name", &instance.name).on_input(Message::NameChanged))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(text(format!("{} {}, {}", instance.loader, instance.version, instance.id)).size(12))
+			.push(Row::new()
+				.push(text("Group").width(Length::Fixed(200.0)))
+				.push(text_input("Ungrouped", instance.group.as_deref().unwrap_or("")).on_input(Message::GroupChanged))
+				.push(checkbox("Favourite", instance.favourite, Message::FavouriteToggled))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(Row::new()
+				.push(text("Notes").width(Length::Fixed(200.0)))
+				.push(text_input("Notes about the instance", &instance.notes).on_input(Message::NotesChanged))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(self.icon_view(instance))
+			.push(text("Launch"))
+			.push(setting(
+				"Wrapper command",
+				"gamemoderun mangohud",
+				&settings.wrapper_command,
+				Message::WrapperChanged
+			))
+			.push(setting(
+				"Pre-launch command",
+				"Aborts the launch if it fails",
+				&settings.pre_launch_command,
+				Message::PreLaunchChanged
+			))
+			.push(setting(
+				"Post-exit command",
+				"Runs after the game exited",
+				&settings.post_exit_command,
+				Message::PostExitChanged
+			))
+			.push(text("Hooks get HZLAUNCHER_INSTANCE_DIR, HZLAUNCHER_VERSION and HZLAUNCHER_EXIT_CODE (post-exit only)").size(12))
+			.push(text("Java"))
+			.push(setting(
+				"Java executable",
+				"java",
+				&settings.java_path,
+				Message::JavaPathChanged
+			))
+			.push(setting(
+				"JVM arguments",
+				"-Xmx4G",
+				&settings.jvm_args,
+				Message::JvmArgsChanged
+			))
+			.push(Row::new()
+				.push(text("Game directory").width(Length::Fixed(200.0)))
+				.push(text_input(
+					"Inside the instance directory",
+					&settings.game_dir.as_ref().map(|dir| dir.display().to_string()).unwrap_or_default()
+				).on_input(Message::GameDirChanged))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(text("Window"))
+			.push(Row::new()
+				.push(text("Resolution").width(Length::Fixed(200.0)))
+				.push(text_input("854", &settings.width.map(|width| width.to_string()).unwrap_or_default())
+					.on_input(Message::WidthChanged)
+					.width(Length::Fixed(80.0)))
+				.push(text("x"))
+				.push(text_input("480", &settings.height.map(|height| height.to_string()).unwrap_or_default())
+					.on_input(Message::HeightChanged)
+					.width(Length::Fixed(80.0)))
+				.push(checkbox("Fullscreen", settings.fullscreen, Message::FullscreenToggled))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(Row::new()
+				.push(text("Environment variables"))
+				.push(button("Add").on_press(Message::EnvAdded))
+				.spacing(10)
+				.align_items(Alignment::Center))
+			.push(env_view(&settings.env))
+			.spacing(10);
+
+		content = content.push(scrollable(form).height(Length::Fill));
+		container(content)
+			.width(Length::Fill)
+			.height(Length::Fill)
+			.into()
+	}
+}
+
+impl SettingsUi {
+	fn icon_view<'a>(&'a self, instance: &'a Instance) -> Element<'a, Message> {
+		let preview: Element<'a, Message> = match instance_icon(instance) {
+			Some(path) => image(path).width(Length::Fixed(48.0)).height(Length::Fixed(48.0)).into(),
+			None => text("No icon").into()
+		};
+		let mut import_button = button("Import PNG");
+		if !self.icon_path.trim().is_empty() {
+			import_button = import_button.on_press(Message::ImportIcon);
+		}
+		let mut mod_icon_button = button("Use mod icon");
+		if self.selected_mod.is_some() {
+			mod_icon_button = mod_icon_button.on_press(Message::UseModIcon);
+		}
+		let mut remove_button = button("Remove");
+		if instance.icon.is_some() {
+			remove_button = remove_button.on_press(Message::RemoveIcon);
+		}
+
+		let mut row = Row::new()
+			.push(text("Icon").width(Length::Fixed(200.0)))
+			.push(preview)
+			.push(text_input("Path to a PNG image", &self.icon_path)
+				.on_input(Message::IconPathChanged)
+				.width(Length::Fixed(240.0)))
+			.push(import_button)
+			.spacing(10)
+			.align_items(Alignment::Center);
+		if !self.mod_files.is_empty() {
+			row = row
+				.push(pick_list(&self.mod_files[..], self.selected_mod.clone(), Message::ModIconSelected)
+					.placeholder("Mod"))
+				.push(mod_icon_button);
+		}
+		row.push(remove_button).into()
+	}
+}
+
+fn setting<'a>(label: &'a str, placeholder: &'a str, value: &'a Option<String>, on_input: fn(String) -> Message) -> Element<'a, Message> {
+	Row::new()
+		.push(text(label).width(Length::Fixed(200.0)))
+		.push(text_input(placeholder, value.as_deref().unwrap_or("")).on_input(on_input))
+		.spacing(10)
+		.align_items(Alignment::Center)
+		.into()
+}
+
+fn env_view(env: &[EnvVar]) -> Element<'_, Message> {
+	let mut list = Column::new().spacing(5);
+	for (index, var) in env.iter().enumerate() {
+		let mut row = Row::new()
+			.push(text_input("NAME", &var.name)
+				.on_input(move |name| Message::EnvNameChanged(index, name))
+				.width(Length::FillPortion(2)))
+			.push(pick_list(&EnvAction::ALL[..], Some(var.action), move |action| Message::EnvActionChanged(index, action)))
+			.spacing(10)
+			.align_items(Alignment::Center);
+		row = if var.action == EnvAction::Unset {
+			row.push(horizontal_space(Length::FillPortion(3)))
+		} else {
+			row.push(text_input("value", &var.value)
+				.on_input(move |value| Message::EnvValueChanged(index, value))
+				.width(Length::FillPortion(3)))
+		};
+		list = list.push(row.push(button("Remove").on_press(Message::EnvRemoved(index))));
+	}
+	list.into()
+}
+
+/// Parses a window dimension, `Some(None)` clears it and `None` rejects the input.
+fn parse_size(value: &str) -> Option<Option<u32>> {
+	let value = value.trim();
+	if value.is_empty() {
+		Some(None)
+	} else {
+		value.parse().ok().filter(|&size| size > 0).map(Some)
+	}
+}
+
+fn non_empty(value: String) -> Option<String> {
+	if value.trim().is_empty() {
+		None
+	} else {
+		Some(value)
+	}
+}